@@ -7,6 +7,7 @@ export declare interface NodeInfo {
   solidMilestoneIndex: number
   pruningIndex: number
   features: string[]
+  minPowScore: number
 }
 
 export declare interface MessageMetadata {
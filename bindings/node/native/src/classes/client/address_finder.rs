@@ -77,7 +77,7 @@ declare_types! {
                 if let Some(range) = &*ref_.range.lock().unwrap() {
                     getter = getter.range(range.clone());
                 }
-                getter.get().map(|addresses| {
+                crate::block_on(getter.get()).map(|addresses| {
                     let addresses: Vec<String> = addresses.iter().map(|a| a.to_bech32()).collect();
                     serde_json::to_string(&addresses).unwrap()
                 })
@@ -6,8 +6,8 @@ use std::{convert::TryInto, num::NonZeroU64, str::FromStr};
 use super::MessageDto;
 
 use iota::{
-    message::prelude::{Address, MessageBuilder, MessageId, UTXOInput},
-    BIP32Path, ClientMiner, Seed,
+    message::prelude::{Address, MessageBuilder, MessageId, Payload, UTXOInput},
+    BIP32Path, ClientMiner, PayloadKind, Seed,
 };
 use neon::prelude::*;
 
@@ -88,7 +88,7 @@ impl Task for ClientTask {
                     for output in outputs {
                         sender = sender.output(output.0.clone(), output.1);
                     }
-                    let message_id = sender.post().await?;
+                    let (message_id, _) = sender.post().await?;
                     serde_json::to_string(&message_id).unwrap()
                 }
                 Api::GetUnspentAddress { seed, path, index } => {
@@ -150,12 +150,14 @@ impl Task for ClientTask {
                             MessageId::from_str(&message.parent1.as_ref().unwrap())?,
                         )
                     };
+                    let payload: Payload = message.payload.clone().try_into()?;
+                    let provider = client.get_pow_provider(PayloadKind::from(Some(&payload)));
                     let message = MessageBuilder::<ClientMiner>::new()
                         .with_network_id(client.get_network_id().await?)
                         .with_parent1(parent1)
                         .with_parent2(parent2)
-                        .with_nonce_provider(client.get_pow_provider(), 4000f64)
-                        .with_payload(message.payload.clone().try_into()?)
+                        .with_nonce_provider(provider, client.get_min_pow_score().await?)
+                        .with_payload(payload)
                         .finish()?;
                     let message_id = client.post_message(&message).await?;
                     serde_json::to_string(&message_id).unwrap()
@@ -1,14 +1,13 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use bech32::FromBase32;
 use iota::{
-    message::prelude::{Address, Ed25519Address, MessageId, UTXOInput},
+    message::prelude::{Address, MessageId, UTXOInput},
     Seed,
 };
 use neon::prelude::*;
 
-use std::{convert::TryInto, str::FromStr};
+use std::str::FromStr;
 
 mod builder;
 pub use builder::*;
@@ -33,24 +32,9 @@ pub use address_finder::JsAddressFinder;
 mod balance_getter;
 pub use balance_getter::JsBalanceGetter;
 
-fn parse_bech32_address(address: String) -> crate::Result<Address> {
-    let address_ed25519 = Vec::from_base32(&bech32::decode(&address)?.1)?;
-    let address = Address::Ed25519(Ed25519Address::new(
-        address_ed25519[1..]
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("invalid address length"))?,
-    ));
-    Ok(address)
-}
-
-/// Parses a bech32 address string.
+/// Parses a bech32 or hex encoded address string.
 fn parse_address(address: String) -> crate::Result<Address> {
-    match parse_bech32_address(address.clone()) {
-        Ok(address) => Ok(address),
-        Err(_) => Ok(Address::Ed25519(Ed25519Address::new(
-            hex::decode(address)?.try_into().expect("invalid address length"),
-        ))),
-    }
+    Ok(iota::parse_address(&address)?)
 }
 
 pub struct ClientWrapper(String);
@@ -1,7 +1,7 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::{BIP32Path, Client, Seed};
+use iota::{api::Account, BIP32Path, Client, Seed};
 
 #[tokio::main]
 async fn main() {
@@ -18,7 +18,18 @@ async fn main() {
 
     println!("Get an unspent address: {:#?}", address);
 
-    let addresses = iota.find_addresses(&seed).path(&path).range(0..3).get().unwrap();
+    let addresses = iota.find_addresses(&seed).path(&path).range(0..3).get().await.unwrap();
 
     println!("List of generated address: {:#?}", addresses);
+
+    // Addresses of the first BIP44 account, m/44'/4218'/0'/change'/index', with their chain and index
+    let addresses = iota
+        .find_addresses(&seed)
+        .account(Account::new(0))
+        .range(0..3)
+        .get_all()
+        .await
+        .unwrap();
+
+    println!("List of generated account address: {:#?}", addresses);
 }
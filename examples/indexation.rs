@@ -1,43 +1,41 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota::{Client, Indexation, Message, Payload};
+use iota::{Client, Payload};
 
 #[tokio::main]
 async fn main() {
-    let index = Indexation::new(String::from("Hello"), String::from("Tangle").as_bytes()).unwrap();
-
     let client = Client::builder()
         .nodes(&["http://localhost:14265"])
         .unwrap()
         .build()
         .unwrap();
 
-    let tips = client.get_tips().await.unwrap();
-
-    let message = Message::builder()
-        .with_network_id(client.get_network_id().await.unwrap())
-        .with_parent1(tips.0)
-        .with_parent2(tips.1)
-        .with_payload(Payload::Indexation(Box::new(index)))
-        .finish()
+    let r = client
+        .message()
+        .with_index("Hello")
+        .with_data("Tangle".as_bytes().to_vec())
+        .post()
+        .await
         .unwrap();
-    println!("message: {:?}", message);
-    let r = client.post_message(&message).await.unwrap();
 
     println!("MessageId {}", r);
 
-    let fetched_messages = client.get_message().index(&"Hello").await.unwrap();
+    // The index is sent as raw bytes and hex encoded for the query, so `b"Hello"` and `"Hello"` are equivalent.
+    let fetched_message_ids = client.get_message().index(b"Hello").await.unwrap();
 
-    println!("{:#?}", fetched_messages);
+    println!("{:#?}", fetched_message_ids);
 
-    let r = client.get_message().data(&fetched_messages[0]).await.unwrap();
+    // Only keep the messages whose index matches byte for byte.
+    let fetched_messages = client.find_messages(&[b"Hello"], &[]).await.unwrap();
 
-    println!("{:#?}", r);
-    if let Payload::Indexation(i) = r.payload().as_ref().unwrap() {
-        println!(
-            "Data: {}",
-            String::from_utf8(hex::decode(i.data()).unwrap()).expect("Found invalid UTF-8")
-        );
+    for message in fetched_messages {
+        println!("{:#?}", message);
+        if let Some(Payload::Indexation(i)) = message.payload() {
+            println!(
+                "Data: {}",
+                String::from_utf8(i.data().to_vec()).expect("Found invalid UTF-8")
+            );
+        }
     }
 }
@@ -0,0 +1,33 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota::{api::Account, keystore, Client};
+
+#[tokio::main]
+async fn main() {
+    let iota = Client::builder() // Crate a client instance builder
+        .node("http://0.0.0.0:14265") // Insert the node here
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Move the hex seed from the environment to a keystore file, once
+    if let Ok(seed) = std::env::var("IOTA_SEED") {
+        keystore::create("seed.json", &hex::decode(seed).unwrap(), "password").unwrap();
+        // Insert your password
+    }
+
+    // The seed is cleared from memory when it's dropped
+    let seed = keystore::open("seed.json", "password").unwrap();
+
+    let address = iota
+        .get_unspent_address(&seed)
+        .account(Account::new(0))
+        .get()
+        .await
+        .unwrap();
+    println!("Get an unspent address: {:#?}", address);
+
+    keystore::export("seed.json", "password", "seed-backup.json", "backup password").unwrap();
+    keystore::change_password("seed.json", "password", "new password").unwrap();
+}
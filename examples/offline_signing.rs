@@ -0,0 +1,61 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota::{
+    api::{derive_address_paths, sign_transaction, AddressPath, PreparedTransaction},
+    BIP32Path, Client, Ed25519Address, Seed,
+};
+use std::{convert::TryInto, num::NonZeroU64};
+
+/// In this example, the transaction is prepared and submitted by an online client, while it's signed by an offline
+/// one holding the seed. The addresses of the seed, the prepared and the signed transactions are serialized to JSON,
+/// so they can be carried between the machines.
+
+#[tokio::main]
+async fn main() {
+    let iota = Client::builder() // Crate a client instance builder
+        .node("http://0.0.0.0:14265") // Insert the node here
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Insert your seed. Since the output amount cannot be zero. The seed must contain non-zero balance.
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b1").unwrap(),
+    )
+    .unwrap();
+
+    // Insert your account path. Note that index must be hardened(like 0', 123').
+    let path = BIP32Path::from_str("m/").unwrap();
+
+    // Offline: export the first addresses of the seed with their path
+    let addresses = serde_json::to_string(&derive_address_paths(&seed, &path, 0..20).await.unwrap()).unwrap();
+
+    // Online: search the inputs on the addresses and build the unsigned transaction, without the seed
+    let addresses: Vec<AddressPath> = serde_json::from_str(&addresses).unwrap();
+    // Insert the address to send to
+    let address = hex::decode("6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1fff").unwrap();
+    let prepared = iota
+        .prepare_transaction(&addresses)
+        .output(
+            Ed25519Address::new(address.try_into().unwrap()).into(),
+            NonZeroU64::new(100).unwrap(),
+        )
+        .prepare()
+        .await
+        .unwrap();
+    let prepared = serde_json::to_string_pretty(&prepared).unwrap();
+    println!("Prepared transaction: {}", prepared);
+
+    // Offline: review the inputs and outputs, then sign. The addresses of the inputs and of the remainder are checked
+    // against their path.
+    let prepared: PreparedTransaction = serde_json::from_str(&prepared).unwrap();
+    let signed = serde_json::to_string(&sign_transaction(prepared, &seed).await.unwrap()).unwrap();
+
+    // Online: attach and post the signed transaction
+    let message_id = iota
+        .submit_transaction(serde_json::from_str(&signed).unwrap())
+        .await
+        .unwrap();
+    println!("Message ID: {}", message_id);
+}
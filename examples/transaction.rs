@@ -186,7 +186,7 @@ async fn main() {
 
     println!("{:#?}", message_id);
     delay_for(Duration::from_millis(15000)).await;
-    let message_metadata = iota.get_message().metadata(&message_id.unwrap()).await;
+    let message_metadata = iota.get_message().metadata(&message_id.unwrap().0).await;
     println!(
         "The ledgerInclusionState: {:?}",
         message_metadata.unwrap().ledger_inclusion_state
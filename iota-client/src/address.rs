@@ -0,0 +1,75 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsing of bech32 and hex encoded addresses.
+
+use crate::{builder::Network, Error, Result};
+
+use bech32::FromBase32;
+use bee_message::prelude::{Address, Ed25519Address};
+
+use std::convert::TryInto;
+
+/// Address type byte of Ed25519 addresses in the bech32 data
+const ED25519_ADDRESS_TYPE: u8 = 0;
+/// Length of Ed25519 addresses
+const ED25519_ADDRESS_LENGTH: usize = 32;
+
+/// Bech32 parsing of addresses.
+pub trait Bech32Address: Sized {
+    /// Parse a bech32 address of any network.
+    fn try_from_bech32(address: &str) -> Result<Self>;
+
+    /// Parse a bech32 address, checking that its HRP belongs to the network.
+    fn try_from_bech32_for_network(address: &str, network: &Network) -> Result<Self>;
+}
+
+impl Bech32Address for Address {
+    fn try_from_bech32(address: &str) -> Result<Self> {
+        let (hrp, address) = decode_bech32(address)?;
+        if !Network::all()
+            .iter()
+            .any(|network| network.bech32_hrps().contains(&hrp.as_str()))
+        {
+            return Err(Error::UnknownBech32Hrp(hrp));
+        }
+        Ok(address)
+    }
+
+    fn try_from_bech32_for_network(address: &str, network: &Network) -> Result<Self> {
+        let (hrp, address) = decode_bech32(address)?;
+        if !network.bech32_hrps().contains(&hrp.as_str()) {
+            return Err(Error::Bech32HrpMismatch(hrp, network.clone()));
+        }
+        Ok(address)
+    }
+}
+
+fn decode_bech32(address: &str) -> Result<(String, Address)> {
+    let (hrp, data) = bech32::decode(address)?;
+    let data = Vec::<u8>::from_base32(&data)?;
+    match data.split_first() {
+        Some((&ED25519_ADDRESS_TYPE, address)) => Ok((hrp, Address::Ed25519(ed25519_address(address)?))),
+        Some((kind, _)) => Err(Error::InvalidAddress(format!("unknown address type {}", kind))),
+        None => Err(Error::InvalidAddress(String::from("empty address"))),
+    }
+}
+
+fn ed25519_address(address: &[u8]) -> Result<Ed25519Address> {
+    Ok(Ed25519Address::new(address.try_into().map_err(|_| {
+        Error::InvalidAddress(format!(
+            "Ed25519 address of {} bytes instead of {}",
+            address.len(),
+            ED25519_ADDRESS_LENGTH
+        ))
+    })?))
+}
+
+/// Parse a bech32 address of any network, or a hex encoded Ed25519 address.
+pub fn parse_address(address: &str) -> Result<Address> {
+    if address.len() == ED25519_ADDRESS_LENGTH * 2 && address.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(Address::Ed25519(ed25519_address(&hex::decode(address)?)?))
+    } else {
+        Address::try_from_bech32(address)
+    }
+}
@@ -1,37 +1,115 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Client, Error, Result};
+use crate::{signing::Signer, Client, Error, Result};
 
 use bee_message::prelude::{Address, Ed25519Address};
-use bee_signing_ext::{
-    binary::{BIP32Path, Ed25519PrivateKey},
-    Seed,
-};
+use bee_signing_ext::binary::BIP32Path;
 use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
 use core::convert::TryInto;
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
 const HARDEND: u32 = 1 << 31;
 
+/// Registered coin type of IOTA in SLIP-0044
+pub const IOTA_COIN_TYPE: u32 = 4218;
+
+/// A BIP44 account of a seed. Its addresses are derived on two chains: the external chain `m/44'/coin'/account'/0'`
+/// for the addresses given to others, and the internal chain `m/44'/coin'/account'/1'` for the remainders of the
+/// transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Account {
+    coin_type: u32,
+    index: u32,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Account {
+    /// Create the account with the given index, using the IOTA coin type
+    pub fn new(index: u32) -> Self {
+        Self {
+            coin_type: IOTA_COIN_TYPE,
+            index,
+        }
+    }
+
+    /// Set the coin type of the account
+    pub fn with_coin_type(mut self, coin_type: u32) -> Self {
+        self.coin_type = coin_type;
+        self
+    }
+
+    /// Coin type of the account
+    pub fn coin_type(&self) -> u32 {
+        self.coin_type
+    }
+
+    /// Index of the account
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Path of the account, `m/44'/coin'/account'`
+    pub fn path(&self) -> BIP32Path {
+        let mut path = BIP32Path::from_str("m/").expect("empty BIP32 path");
+        path.push(44 + HARDEND);
+        path.push(self.coin_type + HARDEND);
+        path.push(self.index + HARDEND);
+        path
+    }
+
+    /// Path of the internal or external chain of the account, `m/44'/coin'/account'/change'`
+    pub fn chain_path(&self, internal: bool) -> BIP32Path {
+        let mut path = self.path();
+        path.push(internal as u32 + HARDEND);
+        path
+    }
+
+    /// Path of the address with the given index on the internal or external chain,
+    /// `m/44'/coin'/account'/change'/index'`
+    pub fn address_path(&self, internal: bool, index: usize) -> BIP32Path {
+        child_path(&self.chain_path(internal), index)
+    }
+}
+
+/// An address of a BIP44 account, with its position in the account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountAddress {
+    /// The address
+    pub address: Address,
+    /// Whether the address is on the internal chain, used for remainders
+    pub internal: bool,
+    /// Index of the address on its chain
+    pub index: usize,
+}
+
 /// Builder of find_addresses API
 pub struct GetAddressesBuilder<'a> {
-    _client: &'a Client,
-    seed: &'a Seed,
+    client: &'a Client,
+    signer: &'a dyn Signer,
     path: Option<&'a BIP32Path>,
+    account: Option<Account>,
+    internal: bool,
     range: Option<Range<usize>>,
 }
 
 impl<'a> GetAddressesBuilder<'a> {
     /// Create find_addresses builder
-    pub fn new(_client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
         Self {
-            _client,
-            seed,
+            client,
+            signer,
             path: None,
+            account: None,
+            internal: false,
             range: None,
         }
     }
@@ -42,6 +120,18 @@ impl<'a> GetAddressesBuilder<'a> {
         self
     }
 
+    /// Set the account to derive the addresses of, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Derive the addresses of the internal chain of the account instead of the external one
+    pub fn internal(mut self, internal: bool) -> Self {
+        self.internal = internal;
+        self
+    }
+
     /// Set range to the builder
     pub fn range(mut self, range: Range<usize>) -> Self {
         self.range = Some(range);
@@ -49,40 +139,164 @@ impl<'a> GetAddressesBuilder<'a> {
     }
 
     /// Consume the builder and get the vector of Address
-    pub fn get(self) -> Result<Vec<Address>> {
-        let mut path = match self.path {
-            Some(p) => p.clone(),
-            None => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+    pub async fn get(self) -> Result<Vec<Address>> {
+        let path = match (self.account, self.path) {
+            (Some(account), _) => account.chain_path(self.internal),
+            (None, Some(p)) => p.clone(),
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
         };
+        let range = self.range.unwrap_or(0..20);
 
-        let range = match self.range {
-            Some(r) => r,
-            None => 0..20,
-        };
+        derive_addresses(self.client, self.signer, &path, range).await
+    }
 
-        let seed = match self.seed {
-            Seed::Ed25519(s) => s,
-            _ => panic!("Other seed scheme isn't supported yet."),
+    /// Consume the builder and get the addresses of both chains of the account, external ones first
+    pub async fn get_all(self) -> Result<Vec<AccountAddress>> {
+        let account = match self.account {
+            Some(account) => account,
+            None => return Err(Error::MissingParameter(String::from("account"))),
         };
+        let range = self.range.unwrap_or(0..20);
 
         let mut addresses = Vec::new();
-        for i in range {
-            path.push(i as u32 + HARDEND);
-            let public_key = Ed25519PrivateKey::generate_from_seed(seed, &path)
-                .expect("Invalid Seed & BIP32Path. Probably because the index of path is not hardened.")
-                .generate_public_key()
-                .to_bytes();
-            // Hash the public key to get the address
-            let mut hasher = VarBlake2b::new(32).unwrap();
-            hasher.update(public_key);
-            let mut result: [u8; 32] = [0; 32];
-            hasher.finalize_variable(|res| {
-                result = res.try_into().expect("Invalid Length of Public Key");
-            });
-            addresses.push(Address::Ed25519(Ed25519Address::new(result)));
-            path.pop();
+        for internal in &[false, true] {
+            let chain =
+                derive_addresses(self.client, self.signer, &account.chain_path(*internal), range.clone()).await?;
+            addresses.extend(
+                chain
+                    .into_iter()
+                    .zip(range.clone())
+                    .map(|(address, index)| AccountAddress {
+                        address,
+                        internal: *internal,
+                        index,
+                    }),
+            );
         }
 
         Ok(addresses)
     }
 }
+
+/// Addresses derived from seeds, per fingerprint of the seed and path of the chain. Once the capacity is reached, no
+/// more addresses are cached.
+#[derive(Debug)]
+pub(crate) struct AddressCache {
+    capacity: usize,
+    len: usize,
+    chains: HashMap<([u8; 32], String), HashMap<usize, Address>>,
+}
+
+impl AddressCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            len: 0,
+            chains: HashMap::new(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    fn chain(&self, fingerprint: &[u8; 32], chain: &str) -> Option<&HashMap<usize, Address>> {
+        self.chains.get(&(*fingerprint, chain.to_string()))
+    }
+
+    fn insert(&mut self, fingerprint: [u8; 32], chain: String, index: usize, address: Address) {
+        if self.len >= self.capacity {
+            return;
+        }
+        if self
+            .chains
+            .entry((fingerprint, chain))
+            .or_default()
+            .insert(index, address)
+            .is_none()
+        {
+            self.len += 1;
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.chains.clear();
+        self.len = 0;
+    }
+}
+
+/// Derive the addresses `path/index'` of the indexes in the range. The addresses missing from the cache of the client
+/// are derived in a single batch of the signer, which an in-memory seed derives across several threads.
+pub(crate) async fn derive_addresses(
+    client: &Client,
+    signer: &dyn Signer,
+    path: &BIP32Path,
+    range: Range<usize>,
+) -> Result<Vec<Address>> {
+    if !client.address_cache.read().unwrap().is_enabled() {
+        return derive_chain_addresses(signer, path, &range.collect::<Vec<usize>>()).await;
+    }
+
+    let fingerprint = signer.fingerprint().await?;
+    let chain = path.to_string();
+    let cached: Vec<Option<Address>> = {
+        let cache = client.address_cache.read().unwrap();
+        let addresses = cache.chain(&fingerprint, &chain);
+        range
+            .clone()
+            .map(|index| addresses.and_then(|addresses| addresses.get(&index).cloned()))
+            .collect()
+    };
+    let missing: Vec<usize> = range
+        .zip(cached.iter())
+        .filter(|(_, address)| address.is_none())
+        .map(|(index, _)| index)
+        .collect();
+    let derived = derive_chain_addresses(signer, path, &missing).await?;
+
+    let mut cache = client.address_cache.write().unwrap();
+    for (index, address) in missing.iter().zip(derived.iter()) {
+        cache.insert(fingerprint, chain.clone(), *index, address.clone());
+    }
+
+    // The derived addresses fill the gaps of the cached ones, in order
+    let mut derived = derived.into_iter();
+    Ok(cached
+        .into_iter()
+        .map(|address| address.or_else(|| derived.next()).expect("missing derived address"))
+        .collect())
+}
+
+// Derive the addresses `path/index'` of the indexes.
+async fn derive_chain_addresses(signer: &dyn Signer, path: &BIP32Path, indexes: &[usize]) -> Result<Vec<Address>> {
+    let paths: Vec<BIP32Path> = indexes.iter().map(|index| child_path(path, *index)).collect();
+    Ok(signer
+        .public_keys(&paths)
+        .await?
+        .iter()
+        .map(address_of_public_key)
+        .collect())
+}
+
+/// Path of the address with the given index under the path, `path/index'`.
+pub(crate) fn child_path(path: &BIP32Path, index: usize) -> BIP32Path {
+    let mut path = path.clone();
+    path.push(index as u32 + HARDEND);
+    path
+}
+
+/// Derive the address of the key with the given path.
+pub(crate) async fn derive_address_of_path(signer: &dyn Signer, path: &BIP32Path) -> Result<Address> {
+    Ok(address_of_public_key(&signer.public_key(path).await?))
+}
+
+fn address_of_public_key(public_key: &[u8; 32]) -> Address {
+    // Hash the public key to get the address
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(public_key);
+    let mut result: [u8; 32] = [0; 32];
+    hasher.finalize_variable(|res| {
+        result = res.try_into().expect("Invalid Length of Public Key");
+    });
+    Address::Ed25519(Ed25519Address::new(result))
+}
@@ -1,26 +1,65 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Client, Error, Result};
+use crate::{
+    api::{discover_chain, Account, UsedAddress, DEFAULT_GAP_LIMIT, MAX_CONCURRENT_REQUESTS},
+    signing::Signer,
+    types::OutputMetadata,
+    Client, Error, Result,
+};
 
-use bee_signing_ext::{binary::BIP32Path, Seed};
+use bee_message::prelude::UTXOInput;
+use bee_signing_ext::binary::BIP32Path;
+use futures::stream::{self, StreamExt, TryStreamExt};
 
 /// Builder of get_balance API
 pub struct GetBalanceBuilder<'a> {
     client: &'a Client,
-    seed: &'a Seed,
+    signer: &'a dyn Signer,
     path: Option<&'a BIP32Path>,
+    account: Option<Account>,
     index: Option<usize>,
+    gap_limit: usize,
+}
+
+/// Balance of the addresses of a seed, with their unspent outputs.
+#[derive(Debug, Serialize)]
+pub struct BalanceReport {
+    /// Used addresses of the searched chains
+    pub addresses: Vec<AddressReport>,
+    /// Sum of the balances of the addresses
+    #[serde(rename = "totalBalance")]
+    pub total_balance: u64,
+    /// Number of unspent outputs of the addresses
+    #[serde(rename = "totalOutputs")]
+    pub total_outputs: usize,
+}
+
+/// Balance of an address of a seed.
+#[derive(Debug, Serialize)]
+pub struct AddressReport {
+    /// Bech32 encoded address
+    pub address: String,
+    /// Index of the address on its chain
+    pub index: usize,
+    /// Whether the address is on the internal chain of the account
+    pub internal: bool,
+    /// Sum of the amounts of the unspent outputs
+    pub balance: u64,
+    /// Unspent outputs of the address
+    pub outputs: Vec<OutputMetadata>,
 }
 
 impl<'a> GetBalanceBuilder<'a> {
     /// Create get_balance builder
-    pub fn new(client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
         Self {
             client,
-            seed,
+            signer,
             path: None,
+            account: None,
             index: None,
+            gap_limit: DEFAULT_GAP_LIMIT,
         }
     }
 
@@ -30,60 +69,104 @@ impl<'a> GetBalanceBuilder<'a> {
         self
     }
 
+    /// Set the account to get the balance of both its external and internal chains, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
     /// Set index to the builder
     pub fn index(mut self, index: usize) -> Self {
         self.index = Some(index);
         self
     }
 
+    /// Set the number of consecutive addresses which never received any output after which the search stops.
+    /// Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
     /// Consume the builder and get the API result
     pub async fn get(self) -> Result<u64> {
-        let path = match self.path {
-            Some(p) => {
+        // Emptied addresses don't stop the search, so the funds after them are found
+        let used = self.discover().await?;
+        Ok(used.iter().map(|address| address.balance).sum())
+    }
+
+    /// Consume the builder and get the balance of each used address with its unspent outputs, and the totals
+    pub async fn get_report(self) -> Result<BalanceReport> {
+        let client = self.client;
+        let used = self.discover().await?;
+
+        // Only the unspent outputs are fetched, a few requests at a time and in order. An emptied address has none.
+        let output_ids: Vec<(usize, Box<[UTXOInput]>)> =
+            stream::iter(used.iter().enumerate().filter(|(_, address)| address.balance != 0))
+                .map(|(position, address)| async move {
+                    Ok::<_, Error>((position, client.get_address().outputs(&address.address).await?))
+                })
+                .buffered(MAX_CONCURRENT_REQUESTS)
+                .try_collect()
+                .await?;
+        let outputs: Vec<(usize, OutputMetadata)> = stream::iter(
+            output_ids
+                .iter()
+                .flat_map(|(position, output_ids)| output_ids.iter().map(move |output_id| (*position, output_id))),
+        )
+        .map(|(position, output_id)| async move { Ok::<_, Error>((position, client.get_output(output_id).await?)) })
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+        let mut unspent: Vec<Vec<OutputMetadata>> = used.iter().map(|_| Vec::new()).collect();
+        for (position, output) in outputs.into_iter().filter(|(_, output)| !output.is_spent) {
+            unspent[position].push(output);
+        }
+
+        let mut addresses = Vec::new();
+        for (address, outputs) in used.into_iter().zip(unspent.into_iter()) {
+            addresses.push(AddressReport {
+                address: address.address.to_bech32(),
+                index: address.index,
+                internal: address.internal,
+                balance: outputs.iter().map(|output| output.amount).sum(),
+                outputs,
+            });
+        }
+
+        Ok(BalanceReport {
+            total_balance: addresses.iter().map(|address| address.balance).sum(),
+            total_outputs: addresses.iter().map(|address| address.outputs.len()).sum(),
+            addresses,
+        })
+    }
+
+    async fn discover(&self) -> Result<Vec<UsedAddress>> {
+        let chains = match (self.account, self.path) {
+            (Some(account), _) => vec![(account.chain_path(false), false), (account.chain_path(true), true)],
+            (None, Some(p)) => {
                 if p.depth() != 2 {
                     return Err(Error::InvalidParameter(String::from(
                         "Must provide BIP32Path with depth of 2",
                     )));
                 }
-                p
+                vec![(p.clone(), false)]
             }
-            None => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
         };
 
-        let mut index = match self.index {
+        let index = match self.index {
             Some(r) => r,
             None => 0,
         };
 
-        // get account balance and check with value
-        let mut balance = 0;
-        loop {
-            let addresses = self
-                .client
-                .find_addresses(self.seed)
-                .path(path)
-                .range(index..index + 20)
-                .get()?;
-
-            // TODO we assume all addressees are unspent and valid if balance > 0
-            let mut end = false;
-            for address in addresses {
-                let address_balance = self.client.get_address().balance(&address).await?;
-                match address_balance {
-                    0 => {
-                        end = true;
-                        break;
-                    }
-                    _ => balance += address_balance,
-                }
-            }
-
-            match end {
-                true => break,
-                false => index += 20,
-            }
+        let mut used = Vec::new();
+        for (path, internal) in chains {
+            let chain = discover_chain(self.client, self.signer, &path, internal, index, self.gap_limit).await?;
+            used.extend(chain.used);
         }
 
-        Ok(balance)
+        Ok(used)
     }
 }
@@ -0,0 +1,551 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    address::parse_address,
+    api::{sign_transaction, transaction_id, Account, RemainderStrategy, SendBuilder, OUTPUT_COUNT_MAX},
+    node::parse_output_id,
+    signing::Signer,
+    types::get_payload_from_json,
+    Client, Error, Result,
+};
+
+use bee_message::prelude::{Address, Input, Message, MessageId, Payload, TransactionId, UTXOInput};
+use bee_signing_ext::binary::BIP32Path;
+
+use std::{num::NonZeroU64, str::FromStr, time::Duration};
+
+/// A payment to a recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payout {
+    /// Bech32 or hex encoded address of the recipient
+    pub address: String,
+    /// Amount paid to the recipient
+    pub amount: u64,
+}
+
+/// Status of a payout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum PayoutStatus {
+    /// The payout wasn't sent yet
+    Pending,
+    /// The transaction paying the recipient was sent, but wasn't confirmed yet
+    Submitted {
+        /// Id of the message of the transaction
+        #[serde(rename = "messageId")]
+        message_id: String,
+    },
+    /// Posting the transaction paying the recipient failed without telling whether the node accepted it. The payout
+    /// isn't sent again until the transaction is found in the ledger, or its inputs spent by another transaction.
+    Unknown {
+        /// Id of the transaction
+        #[serde(rename = "transactionId")]
+        transaction_id: String,
+        /// Output ids of the inputs of the transaction
+        inputs: Vec<String>,
+        /// Error of the posting
+        error: String,
+    },
+    /// The transaction paying the recipient is included in the ledger
+    Confirmed {
+        /// Id of the message of the transaction
+        #[serde(rename = "messageId")]
+        message_id: String,
+    },
+    /// The transaction paying the recipient wasn't posted, or its inputs were spent by another transaction, so the
+    /// payout can be sent again
+    Failed {
+        /// Reason of the failure
+        error: String,
+    },
+}
+
+/// A payout with its status. The results are serializable, so they can be stored to resume the batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayoutResult {
+    /// The payout
+    #[serde(flatten)]
+    pub payout: Payout,
+    /// Status of the payout
+    #[serde(flatten)]
+    pub status: PayoutStatus,
+}
+
+/// Read the payouts of a CSV file with an `address,amount` record per line. A header line is skipped.
+pub fn payouts_from_csv(csv: &str) -> Result<Vec<Payout>> {
+    let mut payouts = Vec::new();
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let amount = match fields.as_slice() {
+            [_, amount] => u64::from_str(amount),
+            _ => return Err(Error::InvalidParameter(format!("payout on line {}", number + 1))),
+        };
+        match amount {
+            Ok(amount) => payouts.push(Payout {
+                address: fields[0].to_string(),
+                amount,
+            }),
+            Err(_) if number == 0 => continue,
+            Err(_) => return Err(Error::InvalidParameter(format!("amount on line {}", number + 1))),
+        }
+    }
+    check_payouts(&payouts)?;
+    Ok(payouts)
+}
+
+/// Read the payouts of a JSON array of `{"address": ..., "amount": ...}` objects.
+pub fn payouts_from_json(json: &str) -> Result<Vec<Payout>> {
+    let payouts: Vec<Payout> = serde_json::from_str(json)?;
+    check_payouts(&payouts)?;
+    Ok(payouts)
+}
+
+fn check_payouts(payouts: &[Payout]) -> Result<()> {
+    for payout in payouts {
+        parse_address(&payout.address)?;
+        if payout.amount == 0 {
+            return Err(Error::InvalidParameter(format!(
+                "zero amount paid to {}",
+                payout.address
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Builder of batch_send API
+pub struct BatchSendBuilder<'a> {
+    client: &'a Client,
+    signer: &'a dyn Signer,
+    path: Option<&'a BIP32Path>,
+    account: Option<Account>,
+    index: Option<usize>,
+    results: Vec<PayoutResult>,
+    resumed: Option<Vec<PayoutResult>>,
+    max_outputs: usize,
+    confirmation_timeout: Duration,
+}
+
+impl<'a> BatchSendBuilder<'a> {
+    /// Create batch_send builder
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
+        Self {
+            client,
+            signer,
+            path: None,
+            account: None,
+            index: None,
+            results: Vec::new(),
+            resumed: None,
+            max_outputs: OUTPUT_COUNT_MAX - 1,
+            confirmation_timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// Set the path of the chain to spend from
+    pub fn path(mut self, path: &'a BIP32Path) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the account to spend from, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Set the address index the search of the inputs starts from
+    pub fn index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Add the payouts to send. A resumed batch already has its payouts, so this can't be combined with `resume()`.
+    pub fn payouts(mut self, payouts: Vec<Payout>) -> Self {
+        self.results.extend(payouts.into_iter().map(|payout| PayoutResult {
+            payout,
+            status: PayoutStatus::Pending,
+        }));
+        self
+    }
+
+    /// Resume a batch from the results of a previous run, which replace the results of an earlier call. Confirmed
+    /// payouts are skipped, submitted ones are awaited and only sent again if their transaction didn't make it into
+    /// the ledger. The results hold the payouts of the batch, so this can't be combined with `payouts()`.
+    pub fn resume(mut self, results: Vec<PayoutResult>) -> Self {
+        self.resumed = Some(results);
+        self
+    }
+
+    /// Set the maximum number of recipients paid by each transaction, at most 126 so there's room for the remainder
+    pub fn max_outputs(mut self, max_outputs: usize) -> Self {
+        self.max_outputs = max_outputs;
+        self
+    }
+
+    /// Set how long to wait for the confirmation of each transaction, which is needed to spend its remainder in the
+    /// next one. Defaults to 5 minutes.
+    pub fn confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.confirmation_timeout = timeout;
+        self
+    }
+
+    /// Consume the builder and send the payouts in sequence, in transactions spending the remainder of the previous
+    /// one. The batch stops at the first failure, whose error is returned with the results, which tell which payouts
+    /// were sent so it can be resumed.
+    pub async fn post(mut self) -> (Vec<PayoutResult>, Option<Error>) {
+        if let Some(resumed) = self.resumed.take() {
+            // Resuming with the payouts given again would pay them twice
+            if !self.results.is_empty() {
+                let error = Error::InvalidParameter(String::from("payouts can't be combined with resume"));
+                return (resumed, Some(error));
+            }
+            self.results = resumed;
+        }
+        let error = self.send_payouts().await.err();
+        (self.results, error)
+    }
+
+    async fn send_payouts(&mut self) -> Result<()> {
+        if self.max_outputs == 0 || self.max_outputs >= OUTPUT_COUNT_MAX {
+            return Err(Error::InvalidParameter(format!(
+                "max outputs must be between 1 and {}",
+                OUTPUT_COUNT_MAX - 1
+            )));
+        }
+        let payouts: Vec<Payout> = self.results.iter().map(|result| result.payout.clone()).collect();
+        check_payouts(&payouts)?;
+        let remainder_path = match (self.account, self.path) {
+            (Some(account), _) => account.chain_path(true),
+            (None, Some(p)) => p.clone(),
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+        };
+
+        // Never pay twice: settle the transactions sent by a previous run first
+        self.await_submitted().await?;
+        self.resolve_unknown().await?;
+
+        let pending: Vec<usize> = (0..self.results.len())
+            .filter(|i| match self.results[*i].status {
+                PayoutStatus::Pending | PayoutStatus::Failed { .. } => true,
+                _ => false,
+            })
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        // The remainders are chained on a reserved address, each transaction spending the one of the previous
+        let (remainder_address, remainder_index) = self
+            .client
+            .get_unspent_address(self.signer)
+            .path(&remainder_path)
+            .reserve(true)
+            .get()
+            .await?;
+        let result = self.send_chain(&pending, &remainder_address, remainder_index).await;
+        self.client.release_address(&remainder_address);
+
+        result
+    }
+
+    async fn send_chain(
+        &mut self,
+        pending: &[usize],
+        remainder_address: &Address,
+        remainder_index: usize,
+    ) -> Result<()> {
+        let batches: Vec<&[usize]> = pending.chunks(self.max_outputs).collect();
+        for (number, batch) in batches.iter().enumerate() {
+            let rest: u64 = batches[number + 1..]
+                .iter()
+                .flat_map(|batch| batch.iter())
+                .map(|i| self.results[*i].payout.amount)
+                .sum();
+
+            let mut sender = self
+                .client
+                .send(self.signer)
+                .remainder(RemainderStrategy::Address(remainder_address.clone()))
+                .min_remainder(rest);
+            sender = match (self.account, self.path) {
+                (Some(account), _) => sender.account(account),
+                (_, Some(path)) => sender.path(path),
+                _ => sender,
+            };
+            if number == 0 {
+                if let Some(index) = self.index {
+                    sender = sender.index(index);
+                }
+            } else {
+                // Spend the confirmed remainder of the previous transaction
+                let inputs: Vec<UTXOInput> = self.client.get_address().outputs(remainder_address).await?.into_vec();
+                sender = sender.inputs(inputs).input_range(remainder_index..remainder_index + 1);
+            }
+            for i in batch.iter() {
+                let payout = &self.results[*i].payout;
+                sender = sender.output(
+                    parse_address(&payout.address)?,
+                    NonZeroU64::new(payout.amount).expect("zero amount"),
+                );
+            }
+
+            // Nothing reached the node until the message is posted, so the payouts can be sent again
+            let message = match self.attach(sender).await {
+                Ok(message) => message,
+                Err(error) => {
+                    self.set_status(
+                        batch,
+                        PayoutStatus::Failed {
+                            error: error.to_string(),
+                        },
+                    );
+                    return Err(error);
+                }
+            };
+            let (id, inputs) = match message.payload() {
+                Some(payload) => transaction_of_payload(payload)?,
+                None => return Err(Error::TransactionError),
+            };
+            let message_id = match self.client.post_message(&message).await {
+                Ok(message_id) => message_id,
+                Err(error) => {
+                    let status = if is_not_posted(&error) {
+                        PayoutStatus::Failed {
+                            error: error.to_string(),
+                        }
+                    } else {
+                        PayoutStatus::Unknown {
+                            transaction_id: id.to_string(),
+                            inputs: inputs.iter().map(|input| input.output_id().to_string()).collect(),
+                            error: error.to_string(),
+                        }
+                    };
+                    self.set_status(batch, status);
+                    return Err(error);
+                }
+            };
+            self.set_status(
+                batch,
+                PayoutStatus::Submitted {
+                    message_id: message_id.to_string(),
+                },
+            );
+            self.await_confirmation(batch, &message_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Prepare and sign the transaction of the sender, and get the message attaching it, with its PoW done.
+    async fn attach(&self, sender: SendBuilder<'a>) -> Result<Message> {
+        let signed = sign_transaction(sender.prepare().await?, self.signer).await?;
+        let payload = get_payload_from_json(signed.payload, None)?;
+        self.client.message().with_payload(payload).finish().await
+    }
+
+    /// Wait for the payouts submitted by a previous run, until they are all confirmed.
+    async fn await_submitted(&mut self) -> Result<()> {
+        let mut submitted: Vec<String> = self
+            .results
+            .iter()
+            .filter_map(|result| match &result.status {
+                PayoutStatus::Submitted { message_id } => Some(message_id.clone()),
+                _ => None,
+            })
+            .collect();
+        submitted.sort();
+        submitted.dedup();
+
+        for message_id in submitted {
+            let batch: Vec<usize> = (0..self.results.len())
+                .filter(|i| match &self.results[*i].status {
+                    PayoutStatus::Submitted { message_id: id } => *id == message_id,
+                    _ => false,
+                })
+                .collect();
+            let message_id = match MessageId::from_str(&message_id) {
+                Ok(message_id) => message_id,
+                Err(_) => {
+                    self.set_status(
+                        &batch,
+                        PayoutStatus::Failed {
+                            error: format!("invalid message id {}", message_id),
+                        },
+                    );
+                    continue;
+                }
+            };
+            self.await_confirmation(&batch, &message_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Find out what became of the transactions of a previous run whose posting failed, until they are all settled.
+    async fn resolve_unknown(&mut self) -> Result<()> {
+        let mut unknown: Vec<(String, Vec<String>)> = self
+            .results
+            .iter()
+            .filter_map(|result| match &result.status {
+                PayoutStatus::Unknown {
+                    transaction_id, inputs, ..
+                } => Some((transaction_id.clone(), inputs.clone())),
+                _ => None,
+            })
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+
+        for (transaction_id, inputs) in unknown {
+            let batch: Vec<usize> = (0..self.results.len())
+                .filter(|i| match &self.results[*i].status {
+                    PayoutStatus::Unknown { transaction_id: id, .. } => *id == transaction_id,
+                    _ => false,
+                })
+                .collect();
+            let mut id = [0u8; 32];
+            hex::decode_to_slice(&transaction_id, &mut id)?;
+            let inputs = inputs
+                .iter()
+                .map(|input| parse_output_id(input))
+                .collect::<Result<Vec<UTXOInput>>>()?;
+            match self.transaction_state(TransactionId::new(id), &inputs).await? {
+                TransactionState::Included(message_id) => self.set_status(
+                    &batch,
+                    PayoutStatus::Confirmed {
+                        message_id: message_id.to_string(),
+                    },
+                ),
+                TransactionState::Conflicting => self.set_status(
+                    &batch,
+                    PayoutStatus::Failed {
+                        error: format!("transaction {} not included: its inputs were spent", transaction_id),
+                    },
+                ),
+                TransactionState::Pending => return Err(Error::TransactionUnresolved(transaction_id)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the confirmation of the transaction paying the batch.
+    async fn await_confirmation(&mut self, batch: &[usize], message_id: &MessageId) -> Result<()> {
+        match self
+            .client
+            .wait_for_confirmation(message_id, self.confirmation_timeout)
+            .await
+        {
+            Ok(message_id) => {
+                self.set_status(
+                    batch,
+                    PayoutStatus::Confirmed {
+                        message_id: message_id.to_string(),
+                    },
+                );
+                Ok(())
+            }
+            // The message is conflicting, which may be because another attachment of its transaction was included
+            Err(Error::MessageNotIncluded(id, state)) => {
+                let message = self.client.get_message().data(message_id).await?;
+                let (transaction_id, inputs) = match message.payload() {
+                    Some(payload) => transaction_of_payload(payload)?,
+                    None => return Err(Error::TransactionError),
+                };
+                match self.transaction_state(transaction_id, &inputs).await? {
+                    TransactionState::Included(message_id) => {
+                        self.set_status(
+                            batch,
+                            PayoutStatus::Confirmed {
+                                message_id: message_id.to_string(),
+                            },
+                        );
+                        return Ok(());
+                    }
+                    // The transaction can't be included anymore, so it's safe to send the payouts again
+                    TransactionState::Conflicting => self.set_status(
+                        batch,
+                        PayoutStatus::Failed {
+                            error: format!("transaction not included: {}", state),
+                        },
+                    ),
+                    // The transaction may still be included, so the payouts stay submitted
+                    TransactionState::Pending => {}
+                }
+                Err(Error::MessageNotIncluded(id, state))
+            }
+            // The transaction may still be confirmed, so the payouts stay submitted
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Find out whether the transaction is in the ledger, or can't be anymore since its inputs were spent by another
+    /// transaction. The node doesn't tell which transaction spent an output, but the outputs of a transaction only
+    /// exist once it's included, so the inputs are checked first: if they are spent and the transaction has no
+    /// outputs afterwards, another transaction spent them.
+    async fn transaction_state(&self, transaction_id: TransactionId, inputs: &[UTXOInput]) -> Result<TransactionState> {
+        let mut spent = false;
+        for input in inputs {
+            spent |= self.client.get_output(input).await?.is_spent;
+        }
+        match self.client.get_output(&UTXOInput::new(transaction_id, 0)?).await {
+            Ok(output) => {
+                let message_id = MessageId::from_str(&hex::encode(&output.message_id))
+                    .map_err(|_| Error::InvalidParameter(format!("message id {}", hex::encode(&output.message_id))))?;
+                Ok(TransactionState::Included(message_id))
+            }
+            Err(Error::ResponseError(404, _)) if spent => Ok(TransactionState::Conflicting),
+            Err(Error::ResponseError(404, _)) => Ok(TransactionState::Pending),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn set_status(&mut self, batch: &[usize], status: PayoutStatus) {
+        for i in batch {
+            self.results[*i].status = status.clone();
+        }
+    }
+}
+
+/// Where a posted transaction stands
+enum TransactionState {
+    /// The transaction is included in the ledger by the message
+    Included(MessageId),
+    /// An input of the transaction was spent by another transaction, so it can't be included anymore
+    Conflicting,
+    /// The transaction may still be included
+    Pending,
+}
+
+/// Get the id of the transaction of the payload, and its inputs.
+fn transaction_of_payload(payload: &Payload) -> Result<(TransactionId, Vec<UTXOInput>)> {
+    let essence = match payload {
+        Payload::Transaction(transaction) => transaction.essence(),
+        _ => return Err(Error::TransactionError),
+    };
+    let inputs = essence
+        .inputs()
+        .iter()
+        .map(|input| match input {
+            Input::UTXO(input) => Ok(input.clone()),
+            _ => Err(Error::TransactionError),
+        })
+        .collect::<Result<Vec<UTXOInput>>>()?;
+
+    Ok((transaction_id(payload)?, inputs))
+}
+
+/// Get whether the error of posting a message tells that the request was never sent, or that the node rejected it.
+fn is_not_posted(error: &Error) -> bool {
+    match error {
+        Error::ResponseError(status, _) => (400..500).contains(status),
+        Error::SyncedNodePoolEmpty | Error::UrlError => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{
+        child_path, discover_chain, input_recorder, sign_transaction, Account, AddressIndexRecorder, PreparedInput,
+        PreparedOutput, PreparedTransaction, DEFAULT_GAP_LIMIT, INPUT_COUNT_MAX, MAX_CONCURRENT_REQUESTS,
+    },
+    signing::Signer,
+    types::OutputMetadata,
+    Client, Error, Result,
+};
+
+use bee_message::prelude::*;
+use bee_signing_ext::binary::BIP32Path;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use std::num::NonZeroU64;
+
+/// Unspent output of the seed to consolidate, with the input spending it
+struct ConsolidationInput {
+    output: OutputMetadata,
+    recorder: AddressIndexRecorder,
+}
+
+/// Builder of consolidate API
+pub struct ConsolidateBuilder<'a> {
+    client: &'a Client,
+    signer: &'a dyn Signer,
+    path: Option<&'a BIP32Path>,
+    account: Option<Account>,
+    index: usize,
+    gap_limit: usize,
+    output_threshold: usize,
+    address: Option<Address>,
+    max_inputs: usize,
+}
+
+impl<'a> ConsolidateBuilder<'a> {
+    /// Create consolidate builder
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
+        Self {
+            client,
+            signer,
+            path: None,
+            account: None,
+            index: 0,
+            gap_limit: DEFAULT_GAP_LIMIT,
+            output_threshold: 0,
+            address: None,
+            max_inputs: INPUT_COUNT_MAX,
+        }
+    }
+
+    /// Set the path of the chain to consolidate
+    pub fn path(mut self, path: &'a BIP32Path) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the account to consolidate, both its external and internal chains, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Set the address index the search of the outputs starts from
+    pub fn index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Set the number of consecutive addresses which never received any output after which the search of the outputs
+    /// stops. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Only consolidate the addresses holding at least this number of unspent outputs. By default, all the unspent
+    /// outputs are consolidated.
+    pub fn output_threshold(mut self, output_threshold: usize) -> Self {
+        self.output_threshold = output_threshold;
+        self
+    }
+
+    /// Set the address receiving the consolidated outputs. Defaults to the first unused address of the internal chain
+    /// of the account, or of the path.
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Set the maximum number of inputs of each consolidation transaction, at most 127
+    pub fn max_inputs(mut self, max_inputs: usize) -> Self {
+        self.max_inputs = max_inputs;
+        self
+    }
+
+    /// Consume the builder and send the consolidation transactions, each one gathering up to `max_inputs` unspent
+    /// outputs into a single output. Get the message ids of the transactions, empty if there was nothing to
+    /// consolidate.
+    pub async fn post(self) -> Result<Vec<MessageId>> {
+        let client = self.client;
+        let signer = self.signer;
+        let mut message_ids = Vec::new();
+        for prepared in self.prepare().await? {
+            let signed = sign_transaction(prepared, signer).await?;
+            message_ids.push(client.submit_transaction(signed).await?);
+        }
+
+        Ok(message_ids)
+    }
+
+    /// Consume the builder and get the unsigned consolidation transactions, to be signed with [`sign_transaction`]
+    /// and posted with `Client::submit_transaction`.
+    pub async fn prepare(self) -> Result<Vec<PreparedTransaction>> {
+        if self.max_inputs == 0 || self.max_inputs > INPUT_COUNT_MAX {
+            return Err(Error::InvalidParameter(format!(
+                "max inputs must be between 1 and {}",
+                INPUT_COUNT_MAX
+            )));
+        }
+
+        let chains = match (self.account, self.path) {
+            (Some(account), _) => vec![(account.chain_path(false), false), (account.chain_path(true), true)],
+            (None, Some(p)) => vec![(p.clone(), false)],
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+        };
+
+        let mut inputs = Vec::new();
+        let mut target = self.address.clone();
+        for (path, internal) in chains.iter() {
+            let chain = discover_chain(self.client, self.signer, path, *internal, self.index, self.gap_limit).await?;
+            // The remainders of an account go to its internal chain, so do the consolidated outputs
+            if target.is_none() && (*internal || self.account.is_none()) {
+                target = Some(chain.unused_address.1.clone());
+            }
+
+            for used in chain.used.into_iter().filter(|used| used.balance != 0) {
+                // The unspent outputs are queried on their own, since the whole history of an address would reach the
+                // maximum number of results of the node first
+                let output_ids = self.client.get_address().outputs(&used.address).await?;
+                let outputs: Vec<OutputMetadata> = stream::iter(output_ids.iter())
+                    .map(|output_id| self.client.get_output(output_id))
+                    .buffered(MAX_CONCURRENT_REQUESTS)
+                    .try_collect()
+                    .await?;
+                let unspent: Vec<OutputMetadata> = outputs
+                    .into_iter()
+                    .filter(|output| !output.is_spent && output.amount != 0)
+                    .collect();
+                if unspent.len() < self.output_threshold {
+                    continue;
+                }
+
+                let address_path = match self.account {
+                    Some(account) => account.address_path(*internal, used.index),
+                    None => child_path(path, used.index),
+                };
+                for output in unspent {
+                    let recorder = input_recorder(&output, address_path.clone())?;
+                    inputs.push(ConsolidationInput { output, recorder });
+                }
+            }
+        }
+        let target = target.ok_or(Error::TransactionError)?;
+
+        let mut prepared = Vec::new();
+        let mut inputs = inputs.into_iter().peekable();
+        while inputs.peek().is_some() {
+            let batch: Vec<ConsolidationInput> = inputs.by_ref().take(self.max_inputs).collect();
+            // A single output already on the target address doesn't need to move
+            if batch.len() == 1 && batch[0].output.address == target {
+                continue;
+            }
+            prepared.push(prepare_consolidation(batch, &target)?);
+        }
+
+        Ok(prepared)
+    }
+}
+
+/// Build the transaction gathering the inputs into a single output on the address.
+fn prepare_consolidation(inputs: Vec<ConsolidationInput>, address: &Address) -> Result<PreparedTransaction> {
+    let mut essence = TransactionEssence::builder();
+    let mut prepared_inputs = Vec::new();
+    let mut amount = 0;
+    for input in inputs {
+        let output_id = match &input.recorder.input {
+            Input::UTXO(utxo_input) => utxo_input.output_id().to_string(),
+            _ => return Err(Error::TransactionError),
+        };
+        prepared_inputs.push(PreparedInput {
+            output_id,
+            address: input.output.address.to_bech32(),
+            amount: input.output.amount,
+            path: input.recorder.address_path.to_string(),
+        });
+        essence = essence.add_input(input.recorder.input);
+        amount += input.output.amount;
+    }
+
+    let essence = essence
+        .add_output(SignatureLockedSingleOutput::new(address.clone(), NonZeroU64::new(amount).unwrap()).into())
+        .finish()?;
+
+    Ok(PreparedTransaction {
+        essence: (&essence).into(),
+        inputs: prepared_inputs,
+        outputs: vec![PreparedOutput {
+            address: address.to_bech32(),
+            amount,
+            remainder: false,
+            path: None,
+        }],
+    })
+}
@@ -0,0 +1,170 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{api::Account, signing::Signer, Client, Error, Result};
+
+use bee_message::prelude::{Address, UTXOInput};
+use bee_signing_ext::binary::BIP32Path;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+/// Default number of consecutive unused addresses after which the search of a chain stops
+pub const DEFAULT_GAP_LIMIT: usize = 20;
+
+/// Maximum number of requests sent to the node at once when fetching the outputs of many addresses
+pub(crate) const MAX_CONCURRENT_REQUESTS: usize = 10;
+
+/// An address of the seed which received outputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsedAddress {
+    /// The address
+    pub address: Address,
+    /// Index of the address on its chain
+    pub index: usize,
+    /// Whether the address is on the internal chain of the account, used for remainders
+    pub internal: bool,
+    /// Current balance of the address
+    pub balance: u64,
+    /// Outputs ever received by the address, spent or not
+    pub outputs: Vec<UTXOInput>,
+}
+
+/// Used addresses of a chain, and its first unused address with its index
+pub(crate) struct ChainDiscovery {
+    pub(crate) used: Vec<UsedAddress>,
+    pub(crate) unused_address: (usize, Address),
+}
+
+/// Builder of discover_account API
+pub struct DiscoverAccountBuilder<'a> {
+    client: &'a Client,
+    signer: &'a dyn Signer,
+    path: Option<&'a BIP32Path>,
+    account: Option<Account>,
+    index: usize,
+    gap_limit: usize,
+}
+
+impl<'a> DiscoverAccountBuilder<'a> {
+    /// Create discover_account builder
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
+        Self {
+            client,
+            signer,
+            path: None,
+            account: None,
+            index: 0,
+            gap_limit: DEFAULT_GAP_LIMIT,
+        }
+    }
+
+    /// Set the path of the chain to search
+    pub fn path(mut self, path: &'a BIP32Path) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the account to search, both its external and internal chains are searched
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Set the address index the search starts from
+    pub fn index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Set the number of consecutive unused addresses after which the search of a chain stops. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Consume the builder and get every used address of the chains with its index and balance, including the
+    /// emptied ones
+    pub async fn get(self) -> Result<Vec<UsedAddress>> {
+        let chains = match (self.account, self.path) {
+            (Some(account), _) => vec![(account.chain_path(false), false), (account.chain_path(true), true)],
+            (None, Some(p)) => vec![(p.clone(), false)],
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+        };
+
+        let mut used = Vec::new();
+        for (path, internal) in chains {
+            let chain = discover_chain(self.client, self.signer, &path, internal, self.index, self.gap_limit).await?;
+            used.extend(chain.used);
+        }
+
+        Ok(used)
+    }
+}
+
+/// Search the chain from the given address index until `gap_limit` consecutive addresses never received any output.
+pub(crate) async fn discover_chain(
+    client: &Client,
+    signer: &dyn Signer,
+    path: &BIP32Path,
+    internal: bool,
+    mut index: usize,
+    gap_limit: usize,
+) -> Result<ChainDiscovery> {
+    if gap_limit == 0 {
+        return Err(Error::InvalidParameter(String::from("gap limit must be at least 1")));
+    }
+
+    let mut used = Vec::new();
+    let mut unused_address = None;
+    let mut unused_count = 0;
+    while unused_count < gap_limit {
+        let addresses = client
+            .find_addresses(signer)
+            .path(path)
+            .range(index..index + gap_limit)
+            .get()
+            .await?;
+
+        // Query the addresses of the batch concurrently, a few at a time since the gap limit may be large. An emptied
+        // address still has its spent outputs, so it's used
+        let outputs: Vec<Box<[UTXOInput]>> = stream::iter(addresses.iter())
+            .map(|address| client.get_address().include_spent(true).outputs(address))
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+        let mut batch = Vec::new();
+        for (offset, (address, outputs)) in addresses.iter().zip(outputs.into_iter()).enumerate() {
+            if outputs.is_empty() {
+                unused_count += 1;
+                unused_address.get_or_insert_with(|| (index + offset, address.clone()));
+                if unused_count == gap_limit {
+                    break;
+                }
+            } else {
+                unused_count = 0;
+                batch.push((index + offset, address, outputs));
+            }
+        }
+
+        let balances: Vec<u64> = stream::iter(batch.iter())
+            .map(|(_, address, _)| client.get_address().balance(address))
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+        for ((address_index, address, outputs), balance) in batch.into_iter().zip(balances.into_iter()) {
+            used.push(UsedAddress {
+                address: address.clone(),
+                index: address_index,
+                internal,
+                balance,
+                outputs: outputs.into_vec(),
+            });
+        }
+        index += gap_limit;
+    }
+
+    Ok(ChainDiscovery {
+        used,
+        unused_address: unused_address.expect("no unused address within the gap limit"),
+    })
+}
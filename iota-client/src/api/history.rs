@@ -0,0 +1,374 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{Account, DEFAULT_GAP_LIMIT, MAX_CONCURRENT_REQUESTS},
+    signing::Signer,
+    types::OutputMetadata,
+    Client, Error, Result,
+};
+
+use bee_message::prelude::{Address, Input, MessageId, Output, Payload, UTXOInput};
+use bee_signing_ext::binary::BIP32Path;
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+/// Direction of a transaction relative to the addresses of the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionDirection {
+    /// Tokens received from other addresses
+    Incoming,
+    /// Tokens sent to other addresses
+    Outgoing,
+    /// Tokens moved between the addresses, e.g. a consolidation
+    Internal,
+}
+
+impl TransactionDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Incoming => "incoming",
+            Self::Outgoing => "outgoing",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+/// A transaction which touched the addresses of the history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    /// Id of the message of the transaction
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// Id of the transaction
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    /// Whether the tokens came in, went out or stayed on the addresses
+    pub direction: TransactionDirection,
+    /// Amount received for an incoming transaction, sent to other addresses for an outgoing one, and moved for an
+    /// internal one
+    pub amount: u64,
+    /// Bech32 encoded addresses of the senders of an incoming transaction, or of the recipients of an outgoing one
+    pub counterparties: Vec<String>,
+    /// Index of the milestone which confirmed the transaction
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: Option<u64>,
+    /// Timestamp of the milestone which confirmed the transaction, in seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+    /// Output id of a spent output whose spending transaction couldn't be found. The record is then an outgoing
+    /// transaction of the amount of the output, with empty ids and no counterparties.
+    #[serde(rename = "unresolvedOutput")]
+    pub unresolved_output: Option<String>,
+}
+
+/// Transactions which touched a set of addresses, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionHistory {
+    /// The transactions
+    pub transactions: Vec<TransactionRecord>,
+}
+
+impl TransactionHistory {
+    /// Export the transactions as a JSON array
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.transactions)?)
+    }
+
+    /// Export the transactions as CSV with a header line. The counterparties are separated by `;`, and the milestone
+    /// fields are empty for the transactions of the genesis snapshot and the unresolved ones.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "messageId,transactionId,direction,amount,counterparties,milestoneIndex,timestamp,unresolvedOutput\n",
+        );
+        for transaction in &self.transactions {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                transaction.message_id,
+                transaction.transaction_id,
+                transaction.direction.as_str(),
+                transaction.amount,
+                transaction.counterparties.join(";"),
+                transaction
+                    .milestone_index
+                    .map(|index| index.to_string())
+                    .unwrap_or_default(),
+                transaction
+                    .timestamp
+                    .map(|timestamp| timestamp.to_string())
+                    .unwrap_or_default(),
+                transaction.unresolved_output.as_deref().unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+}
+
+/// Builder of get_transaction_history API
+pub struct GetTransactionHistoryBuilder<'a> {
+    client: &'a Client,
+    signer: &'a dyn Signer,
+    path: Option<&'a BIP32Path>,
+    account: Option<Account>,
+    index: usize,
+    gap_limit: usize,
+}
+
+impl<'a> GetTransactionHistoryBuilder<'a> {
+    /// Create get_transaction_history builder
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
+        Self {
+            client,
+            signer,
+            path: None,
+            account: None,
+            index: 0,
+            gap_limit: DEFAULT_GAP_LIMIT,
+        }
+    }
+
+    /// Set the path of the chain to get the history of
+    pub fn path(mut self, path: &'a BIP32Path) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Set the account to get the history of both its external and internal chains, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Set the address index the search starts from
+    pub fn index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Set the number of consecutive unused addresses after which the search of a chain stops. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Consume the builder and get the transactions which touched the used addresses of the chains
+    pub async fn get(self) -> Result<TransactionHistory> {
+        let mut discovery = self
+            .client
+            .discover_account(self.signer)
+            .index(self.index)
+            .gap_limit(self.gap_limit);
+        if let Some(account) = self.account {
+            discovery = discovery.account(account);
+        }
+        if let Some(path) = self.path {
+            discovery = discovery.path(path);
+        }
+        let used = discovery.get().await?;
+
+        let addresses: Vec<Address> = used.iter().map(|used| used.address.clone()).collect();
+        let output_ids: Vec<UTXOInput> = used.into_iter().flat_map(|used| used.outputs).collect();
+        collect_history(self.client, &addresses, &output_ids).await
+    }
+}
+
+/// Classify a transaction from the addresses and amounts of its inputs and outputs: incoming when it spent no owned
+/// output, internal when it sent nothing to other addresses, and outgoing otherwise. Get the direction, the amount
+/// received, moved or sent to other addresses, and the bech32 encoded senders or recipients.
+pub fn classify_transaction(
+    owned: &[Address],
+    inputs: &[(Address, u64)],
+    outputs: &[(Address, u64)],
+) -> (TransactionDirection, u64, Vec<String>) {
+    let mut spent = 0;
+    let mut senders = Vec::new();
+    for (address, amount) in inputs {
+        if owned.contains(address) {
+            spent += amount;
+        } else if !senders.contains(&address.to_bech32()) {
+            senders.push(address.to_bech32());
+        }
+    }
+
+    let mut received = 0;
+    let mut sent = 0;
+    let mut recipients = Vec::new();
+    for (address, amount) in outputs {
+        if owned.contains(address) {
+            received += amount;
+        } else {
+            sent += amount;
+            if !recipients.contains(&address.to_bech32()) {
+                recipients.push(address.to_bech32());
+            }
+        }
+    }
+
+    match (spent, sent) {
+        (0, _) => (TransactionDirection::Incoming, received, senders),
+        (_, 0) => (TransactionDirection::Internal, received, Vec::new()),
+        _ => (TransactionDirection::Outgoing, sent, recipients),
+    }
+}
+
+/// Build the history of the addresses from the outputs they ever received. A transaction is found through the
+/// outputs it created on the addresses. The node doesn't tell which transaction spent an output, so a spent output
+/// which isn't an input of a found transaction, spent without a remainder sent back to the addresses, is recorded as
+/// an unresolved outgoing transaction.
+pub(crate) async fn collect_history(
+    client: &Client,
+    addresses: &[Address],
+    output_ids: &[UTXOInput],
+) -> Result<TransactionHistory> {
+    let outputs: Vec<OutputMetadata> = stream::iter(output_ids.iter())
+        .map(|output_id| client.get_output(output_id))
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+    let mut message_ids: Vec<&[u8]> = outputs.iter().map(|output| &output.message_id[..]).collect();
+    message_ids.sort();
+    message_ids.dedup();
+    let found: Vec<_> = stream::iter(message_ids.into_iter())
+        .map(|message_id| transaction_record(client, addresses, &outputs, message_id))
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+    let spending: HashSet<String> = found.iter().flat_map(|(_, inputs)| inputs.iter().cloned()).collect();
+    let mut transactions: Vec<TransactionRecord> = found.into_iter().map(|(transaction, _)| transaction).collect();
+
+    for (output_id, output) in output_ids.iter().zip(outputs.iter()) {
+        let output_id = output_id.output_id().to_string();
+        if output.is_spent && !spending.contains(&output_id) {
+            transactions.push(TransactionRecord {
+                message_id: String::new(),
+                transaction_id: String::new(),
+                direction: TransactionDirection::Outgoing,
+                amount: output.amount,
+                counterparties: Vec::new(),
+                milestone_index: None,
+                timestamp: None,
+                unresolved_output: Some(output_id),
+            });
+        }
+    }
+
+    // Several transactions are usually confirmed by the same milestone
+    let mut milestone_indexes: Vec<u64> = transactions
+        .iter()
+        .filter_map(|transaction| transaction.milestone_index)
+        .collect();
+    milestone_indexes.sort();
+    milestone_indexes.dedup();
+    let milestones: Vec<_> = stream::iter(milestone_indexes.iter())
+        .map(|index| client.get_milestone(*index))
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+    let timestamps: HashMap<u64, u64> = milestones
+        .into_iter()
+        .map(|milestone| (milestone.milestone_index, milestone.timestamp))
+        .collect();
+    for transaction in transactions.iter_mut() {
+        transaction.timestamp = transaction
+            .milestone_index
+            .and_then(|index| timestamps.get(&index).copied());
+    }
+
+    // The unresolved transactions come last, since it's unknown when they were confirmed
+    transactions.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+
+    Ok(TransactionHistory { transactions })
+}
+
+/// Order of the transactions in the history
+fn sort_key(transaction: &TransactionRecord) -> (bool, Option<u64>, &str, Option<&str>) {
+    (
+        transaction.unresolved_output.is_some(),
+        transaction.milestone_index,
+        &transaction.message_id,
+        transaction.unresolved_output.as_deref(),
+    )
+}
+
+/// Classify the transaction of the message from its inputs and outputs on the owned addresses. Get it with the output
+/// ids of its inputs.
+async fn transaction_record(
+    client: &Client,
+    owned: &[Address],
+    outputs: &[OutputMetadata],
+    message_id: &[u8],
+) -> Result<(TransactionRecord, Vec<String>)> {
+    let created: Vec<&OutputMetadata> = outputs
+        .iter()
+        .filter(|output| output.message_id == message_id)
+        .collect();
+    let transaction_id = hex::encode(&created[0].transaction_id);
+
+    // The outputs of the genesis snapshot weren't created by a message
+    if message_id.iter().all(|byte| *byte == 0) {
+        let record = TransactionRecord {
+            message_id: hex::encode(message_id),
+            transaction_id,
+            direction: TransactionDirection::Incoming,
+            amount: created.iter().map(|output| output.amount).sum(),
+            counterparties: Vec::new(),
+            milestone_index: None,
+            timestamp: None,
+            unresolved_output: None,
+        };
+        return Ok((record, Vec::new()));
+    }
+
+    let message_id = MessageId::from_str(&hex::encode(message_id))
+        .map_err(|_| Error::InvalidParameter(format!("message id {}", hex::encode(message_id))))?;
+    let message = client.get_message().data(&message_id).await?;
+    let metadata = client.get_message().metadata(&message_id).await?;
+    let essence = match message.payload() {
+        Some(Payload::Transaction(transaction)) => transaction.essence(),
+        _ => return Err(Error::TransactionError),
+    };
+
+    let input_ids: Vec<UTXOInput> = essence
+        .inputs()
+        .iter()
+        .map(|input| match input {
+            Input::UTXO(input) => Ok(input.clone()),
+            _ => Err(Error::TransactionError),
+        })
+        .collect::<Result<_>>()?;
+    let inputs: Vec<OutputMetadata> = stream::iter(input_ids.iter())
+        .map(|input| client.get_output(input))
+        .buffered(MAX_CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+    let inputs: Vec<(Address, u64)> = inputs.into_iter().map(|input| (input.address, input.amount)).collect();
+    let essence_outputs: Vec<(Address, u64)> = essence
+        .outputs()
+        .iter()
+        .filter_map(|output| match output {
+            Output::SignatureLockedSingle(output) => Some((output.address().clone(), output.amount().get())),
+            _ => None,
+        })
+        .collect();
+    let (direction, amount, counterparties) = classify_transaction(owned, &inputs, &essence_outputs);
+
+    let record = TransactionRecord {
+        message_id: message_id.to_string(),
+        transaction_id,
+        direction,
+        amount,
+        counterparties,
+        milestone_index: metadata.referenced_by_milestone_index,
+        timestamp: None,
+        unresolved_output: None,
+    };
+    Ok((
+        record,
+        input_ids.iter().map(|input| input.output_id().to_string()).collect(),
+    ))
+}
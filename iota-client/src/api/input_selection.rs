@@ -0,0 +1,215 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, Result};
+
+/// Maximum number of inputs of a transaction
+pub const INPUT_COUNT_MAX: usize = 127;
+/// Maximum number of outputs of a transaction
+pub const OUTPUT_COUNT_MAX: usize = 127;
+
+/// Number of branches explored by [`BranchAndBound`] before it falls back to [`LargestFirst`]
+const BRANCH_AND_BOUND_TRIES: usize = 100_000;
+
+/// Strategy selecting the unspent outputs used as inputs of a transaction.
+pub trait InputSelection: Send + Sync {
+    /// Select the candidates covering the amount with at most `max_inputs` inputs, given the amounts of the
+    /// candidates in discovery order. Returns the indexes of the selected candidates.
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>>;
+}
+
+/// Select the candidates in discovery order until the amount is covered. Falls back to [`LargestFirst`] if it needs
+/// too many inputs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstFit;
+
+impl InputSelection for FirstFit {
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>> {
+        check_limits(candidates, amount, max_inputs)?;
+        let order: Vec<usize> = (0..candidates.len()).collect();
+        match take_until_covered(candidates, &order, amount) {
+            selection if selection.len() <= max_inputs => Ok(selection),
+            _ => LargestFirst.select(candidates, amount, max_inputs),
+        }
+    }
+}
+
+/// Select the largest candidates first, using as few inputs as possible.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LargestFirst;
+
+impl InputSelection for LargestFirst {
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>> {
+        check_limits(candidates, amount, max_inputs)?;
+        Ok(take_until_covered(candidates, &descending(candidates), amount))
+    }
+}
+
+/// Select the smallest candidates first, consolidating dust. When the smallest ones need too many inputs, the
+/// smallest candidates which can still cover the amount within the limit are selected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmallestFirst;
+
+impl InputSelection for SmallestFirst {
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>> {
+        check_limits(candidates, amount, max_inputs)?;
+        let mut order = descending(candidates);
+        order.reverse();
+
+        // Skip the smallest candidates until a window of `max_inputs` candidates covers the amount, which exists
+        // since the limits were checked.
+        let mut skip = 0;
+        while order[skip..]
+            .iter()
+            .take(max_inputs)
+            .map(|i| candidates[*i])
+            .sum::<u64>()
+            < amount
+        {
+            skip += 1;
+        }
+
+        Ok(take_until_covered(candidates, &order[skip..], amount))
+    }
+}
+
+/// Select a single candidate matching the amount exactly, so no remainder is needed. Otherwise select the smallest
+/// candidate covering the amount on its own, and fall back to [`LargestFirst`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatchPreferred;
+
+impl InputSelection for ExactMatchPreferred {
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>> {
+        check_limits(candidates, amount, max_inputs)?;
+        if let Some(index) = candidates.iter().position(|candidate| *candidate == amount) {
+            return Ok(vec![index]);
+        }
+        let single = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| **candidate > amount)
+            .min_by_key(|(_, candidate)| **candidate);
+        match single {
+            Some((index, _)) => Ok(vec![index]),
+            None => LargestFirst.select(candidates, amount, max_inputs),
+        }
+    }
+}
+
+/// Search the selection with the smallest remainder, preferring fewer inputs on ties, by exploring the subsets of the
+/// candidates with a branch and bound search. Falls back to [`LargestFirst`] if the search is too long to complete.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BranchAndBound;
+
+struct Search<'a> {
+    candidates: &'a [u64],
+    order: Vec<usize>,
+    // Sum of the candidates from each position of the order to the end
+    remaining: Vec<u64>,
+    amount: u64,
+    max_inputs: usize,
+    tries: usize,
+    best: Option<(u64, Vec<usize>)>,
+}
+
+impl<'a> Search<'a> {
+    fn explore(&mut self, position: usize, sum: u64, selection: &mut Vec<usize>) {
+        if self.tries == 0 {
+            return;
+        }
+        self.tries -= 1;
+
+        if sum >= self.amount {
+            let change = sum - self.amount;
+            let better = match &self.best {
+                Some((best_change, best)) => {
+                    change < *best_change || (change == *best_change && selection.len() < best.len())
+                }
+                None => true,
+            };
+            if better {
+                self.best = Some((change, selection.clone()));
+            }
+            return;
+        }
+        // Bound: out of candidates, too many inputs, or the amount can't be reached anymore
+        if position == self.order.len()
+            || selection.len() == self.max_inputs
+            || sum + self.remaining[position] < self.amount
+        {
+            return;
+        }
+        // Bound: the selection can't get a smaller change than the best one
+        if let Some((0, best)) = &self.best {
+            if best.len() <= selection.len() + 1 {
+                return;
+            }
+        }
+
+        let index = self.order[position];
+        selection.push(index);
+        self.explore(position + 1, sum + self.candidates[index], selection);
+        selection.pop();
+        self.explore(position + 1, sum, selection);
+    }
+}
+
+impl InputSelection for BranchAndBound {
+    fn select(&self, candidates: &[u64], amount: u64, max_inputs: usize) -> Result<Vec<usize>> {
+        check_limits(candidates, amount, max_inputs)?;
+        let order = descending(candidates);
+        let mut remaining = vec![0; order.len() + 1];
+        for position in (0..order.len()).rev() {
+            remaining[position] = remaining[position + 1] + candidates[order[position]];
+        }
+
+        let mut search = Search {
+            candidates,
+            order,
+            remaining,
+            amount,
+            max_inputs,
+            tries: BRANCH_AND_BOUND_TRIES,
+            best: None,
+        };
+        search.explore(0, 0, &mut Vec::new());
+
+        match search.best {
+            Some((_, selection)) if search.tries > 0 => Ok(selection),
+            _ => LargestFirst.select(candidates, amount, max_inputs),
+        }
+    }
+}
+
+/// Check that the candidates can cover the amount with at most `max_inputs` inputs.
+fn check_limits(candidates: &[u64], amount: u64, max_inputs: usize) -> Result<()> {
+    if candidates.iter().sum::<u64>() < amount {
+        return Err(Error::NotEnoughBalance(candidates.iter().sum()));
+    }
+    let order = descending(candidates);
+    if order.iter().take(max_inputs).map(|i| candidates[*i]).sum::<u64>() < amount {
+        return Err(Error::TooManyInputs(max_inputs));
+    }
+    Ok(())
+}
+
+/// Indexes of the candidates from the largest to the smallest one.
+fn descending(candidates: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| candidates[*b].cmp(&candidates[*a]));
+    order
+}
+
+/// Take the candidates in the given order until the amount is covered.
+fn take_until_covered(candidates: &[u64], order: &[usize], amount: u64) -> Vec<usize> {
+    let mut sum = 0;
+    let mut selection = Vec::new();
+    for index in order {
+        if sum >= amount {
+            break;
+        }
+        sum += candidates[*index];
+        selection.push(*index);
+    }
+    selection
+}
@@ -0,0 +1,231 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    encryption::{self, PublicKey},
+    pow::{PayloadKind, PowHandle},
+    Client, ClientMiner, Error, Result,
+};
+
+use bee_message::prelude::{Indexation, Message, MessageBuilder, MessageId, Payload};
+
+use std::time::{Duration, Instant};
+
+/// Default time after which the PoW is started again with fresh tips, so messages aren't attached to stale parents.
+pub const TIPS_REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Builder of the message API, which fetches the tips and the network id and does the PoW.
+pub struct ClientMessageBuilder<'a> {
+    client: &'a Client,
+    index: Option<String>,
+    data: Option<Vec<u8>>,
+    recipients: Option<Vec<PublicKey>>,
+    payload: Option<Payload>,
+    parents: Option<(MessageId, MessageId)>,
+    pow_score: Option<f64>,
+    pow_handle: Option<PowHandle>,
+    pow_timeout: Option<Duration>,
+}
+
+impl<'a> ClientMessageBuilder<'a> {
+    /// Create message builder
+    pub fn new(client: &'a Client) -> Self {
+        Self {
+            client,
+            index: None,
+            data: None,
+            recipients: None,
+            payload: None,
+            parents: None,
+            pow_score: None,
+            pow_handle: None,
+            pow_timeout: None,
+        }
+    }
+
+    /// Set the index of an indexation payload to the builder. The upstream `Indexation::new` only takes a `String`
+    /// index, so unlike `get_message().index()` which searches any bytes, only UTF-8 indexes can be published.
+    pub fn with_index<I: Into<String>>(mut self, index: I) -> Self {
+        self.index = Some(index.into());
+        self
+    }
+
+    /// Set the data of an indexation payload to the builder
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Encrypt the data of the indexation payload for the recipients, so only they can read it with
+    /// `encryption::decrypt_message()`
+    pub fn with_encryption(mut self, recipients: &[PublicKey]) -> Self {
+        self.recipients = Some(recipients.to_vec());
+        self
+    }
+
+    /// Set a payload to the builder, it can't be combined with `with_index()`, `with_data()` and `with_encryption()`
+    pub fn with_payload(mut self, payload: Payload) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Set the parents to the builder instead of fetching tips. Tips are then never refreshed.
+    pub fn with_parents(mut self, parent1: MessageId, parent2: MessageId) -> Self {
+        self.parents = Some((parent1, parent2));
+        self
+    }
+
+    /// Set the target PoW score to the builder, instead of the minimum PoW score of the network
+    pub fn with_pow_score(mut self, pow_score: f64) -> Self {
+        self.pow_score = Some(pow_score);
+        self
+    }
+
+    /// Set a handle to the builder, to cancel the PoW or follow its progress from another task or thread
+    pub fn with_pow_handle(mut self, pow_handle: PowHandle) -> Self {
+        self.pow_handle = Some(pow_handle);
+        self
+    }
+
+    /// Set the maximum time the PoW may take to the builder, after which it fails with `Error::PowTimeout`
+    pub fn with_pow_timeout(mut self, pow_timeout: Duration) -> Self {
+        self.pow_timeout = Some(pow_timeout);
+        self
+    }
+
+    /// Consume the builder and post the message
+    pub async fn post(self) -> Result<MessageId> {
+        let client = self.client;
+        let message = self.finish().await?;
+        client.post_message(&message).await
+    }
+
+    /// Consume the builder and get the message ready to be posted
+    pub async fn finish(self) -> Result<Message> {
+        let payload = match (self.payload, self.index, self.data) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                return Err(Error::InvalidParameter(String::from(
+                    "payload can't be combined with index or data",
+                )))
+            }
+            (Some(_), None, None) if self.recipients.is_some() => {
+                return Err(Error::InvalidParameter(String::from("payload can't be encrypted")))
+            }
+            (Some(payload), None, None) => Some(payload),
+            (None, Some(index), data) => {
+                let data = data.unwrap_or_default();
+                let indexation = match &self.recipients {
+                    Some(recipients) => encryption::encrypt_indexation(index, &data, recipients)?,
+                    None => Indexation::new(index, &data)?,
+                };
+                Some(Payload::Indexation(Box::new(indexation)))
+            }
+            (None, None, Some(_)) => return Err(Error::MissingParameter(String::from("index"))),
+            (None, None, None) if self.recipients.is_some() => {
+                return Err(Error::MissingParameter(String::from("index")))
+            }
+            (None, None, None) => None,
+        };
+        let network_id = self.client.get_network_id().await?;
+        let pow_score = match self.pow_score {
+            Some(pow_score) => pow_score,
+            None => self.client.get_min_pow_score().await?,
+        };
+
+        let kind = PayloadKind::from(payload.as_ref());
+
+        let handle = self.pow_handle.unwrap_or_default();
+        handle.start(self.client.get_pow_hash_rate());
+        let deadline = self.pow_timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            if handle.is_cancelled() {
+                return Err(Error::PowCancelled);
+            }
+            let parents = match self.parents {
+                Some(parents) => parents,
+                None => self.client.get_tips().await?,
+            };
+            // The attempt is interrupted when it's abandoned, also when this future is dropped
+            let _interrupt = InterruptOnDrop(&handle);
+            let provider = self.client.get_pow_provider_with_handle(kind, handle.clone());
+            let payload = payload.clone();
+            let attempt = handle.clone();
+
+            let mut pow = tokio::task::spawn_blocking(move || {
+                let mut builder = MessageBuilder::<ClientMiner>::new()
+                    .with_network_id(network_id)
+                    .with_parent1(parents.0)
+                    .with_parent2(parents.1)
+                    .with_nonce_provider(provider, pow_score);
+                if let Some(payload) = payload {
+                    builder = builder.with_payload(payload);
+                }
+                // The builder only tells that no nonce was found, the provider recorded why on the handle
+                builder.finish().map_err(|e| match attempt.take_failure() {
+                    Some(failure) => Error::Pow(failure),
+                    None => Error::MessageError(e),
+                })
+            });
+
+            // The PoW is interrupted when the tips become stale, unless the parents were given, or when the deadline
+            // is reached.
+            let tips_deadline = match self.parents {
+                Some(_) => None,
+                None => Some(Instant::now() + self.client.tips_interval),
+            };
+            let interrupt_at = match (tips_deadline, deadline) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+
+            let result = match interrupt_at {
+                Some(interrupt_at) => {
+                    let wait = interrupt_at.saturating_duration_since(Instant::now());
+                    match tokio::time::timeout(wait, &mut pow).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            handle.interrupt();
+                            // Wait for the workers to stop, the message may also have been finished meanwhile.
+                            if let Ok(Ok(message)) = pow.await {
+                                return pow_done(self.client, &handle, message);
+                            }
+                            if handle.is_cancelled() {
+                                return Err(Error::PowCancelled);
+                            }
+                            if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                                return Err(Error::PowTimeout);
+                            }
+                            handle.restart();
+                            continue;
+                        }
+                    }
+                }
+                None => pow.await,
+            };
+
+            return match result.map_err(|e| Error::Pow(e.to_string()))? {
+                Ok(message) => pow_done(self.client, &handle, message),
+                Err(_) if handle.is_cancelled() => Err(Error::PowCancelled),
+                Err(e) => Err(e),
+            };
+        }
+    }
+}
+
+/// Record the hash rate measured by the finished PoW and return the message.
+fn pow_done(client: &Client, handle: &PowHandle, message: Message) -> Result<Message> {
+    if let Some(hash_rate) = handle.hash_rate() {
+        *client.pow_hash_rate.write().unwrap() = Some(hash_rate);
+    }
+    Ok(message)
+}
+
+/// Interrupts the current PoW attempt when dropped, so no miner is left running once the attempt is abandoned.
+struct InterruptOnDrop<'a>(&'a PowHandle);
+
+impl Drop for InterruptOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.interrupt();
+    }
+}
@@ -5,10 +5,26 @@
 
 mod address;
 mod balance;
+mod batch;
+mod consolidate;
+mod discovery;
+mod history;
+mod input_selection;
+mod message;
+mod notarize;
 mod send;
+mod transaction;
 mod unspent;
 
 pub use address::*;
 pub use balance::*;
+pub use batch::*;
+pub use consolidate::*;
+pub use discovery::*;
+pub use history::*;
+pub use input_selection::*;
+pub use message::*;
+pub use notarize::*;
 pub use send::*;
+pub use transaction::*;
 pub use unspent::*;
@@ -0,0 +1,214 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client::hash_reader, Client, Error, Result};
+
+use bee_message::prelude::{MessageId, Payload};
+use serde_json::Value;
+
+use std::{
+    io::{self, Read},
+    str::FromStr,
+};
+
+/// Default index of notarization messages.
+pub const NOTARIZATION_INDEX: &str = "NOTARIZATION";
+/// Hash algorithm used for notarization.
+pub const NOTARIZATION_ALGORITHM: &str = "blake2b-256";
+
+const NOTARIZATION_VERSION: u8 = 1;
+
+/// The notarization record stored as JSON in the indexation payload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Notarization {
+    version: u8,
+    algorithm: String,
+    hash: String,
+    timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    metadata: Option<Value>,
+}
+
+/// Receipt of a notarized document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotarizationReceipt {
+    /// Message ID of the notarization message
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    /// Index of the notarization message
+    pub index: String,
+    /// Hash algorithm
+    pub algorithm: String,
+    /// Hex encoded hash of the document
+    pub hash: String,
+    /// UNIX timestamp in seconds of the notarization
+    pub timestamp: i64,
+    /// Metadata stored along with the hash
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metadata: Option<Value>,
+}
+
+/// Result of `verify_notarization()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotarizationVerification {
+    /// Whether the hash of the document matches the receipt
+    #[serde(rename = "hashMatches")]
+    pub hash_matches: bool,
+    /// Whether the notarization message on the Tangle matches the receipt
+    #[serde(rename = "messageMatches")]
+    pub message_matches: bool,
+    /// Index of the milestone which referenced the notarization message, if inclusion was checked
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: Option<u64>,
+    /// Timestamp of the milestone which referenced the notarization message, if inclusion was checked
+    #[serde(rename = "milestoneTimestamp")]
+    pub milestone_timestamp: Option<u64>,
+}
+
+impl NotarizationVerification {
+    /// Whether the document is verified: hashes and message match, and the message is referenced by a milestone if
+    /// inclusion was checked.
+    pub fn is_valid(&self, inclusion_required: bool) -> bool {
+        self.hash_matches && self.message_matches && (!inclusion_required || self.milestone_index.is_some())
+    }
+}
+
+/// Builder of notarize API
+pub struct NotarizeBuilder<'a, R> {
+    client: &'a Client,
+    reader: R,
+    index: String,
+    metadata: Option<Value>,
+}
+
+impl<'a, R: Read + Send + 'static> NotarizeBuilder<'a, R> {
+    /// Create notarize builder
+    pub fn new(client: &'a Client, reader: R) -> Self {
+        Self {
+            client,
+            reader,
+            index: NOTARIZATION_INDEX.to_string(),
+            metadata: None,
+        }
+    }
+
+    /// Set the index of the notarization message to the builder, a UTF-8 string like any published index
+    pub fn index<I: Into<String>>(mut self, index: I) -> Self {
+        self.index = index.into();
+        self
+    }
+
+    /// Set metadata stored along with the hash to the builder
+    pub fn metadata(mut self, metadata: Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Consume the builder, hash the document and post the notarization message
+    pub async fn post(self) -> Result<NotarizationReceipt> {
+        let notarization = Notarization {
+            version: NOTARIZATION_VERSION,
+            algorithm: NOTARIZATION_ALGORITHM.to_string(),
+            hash: hex::encode(hash_document(self.reader).await?),
+            timestamp: chrono::Utc::now().timestamp(),
+            metadata: self.metadata,
+        };
+
+        let message_id = self
+            .client
+            .message()
+            .with_index(self.index.clone())
+            .with_data(serde_json::to_vec(&notarization)?)
+            .post()
+            .await?;
+
+        Ok(NotarizationReceipt {
+            message_id: message_id.to_string(),
+            index: self.index,
+            algorithm: notarization.algorithm,
+            hash: notarization.hash,
+            timestamp: notarization.timestamp,
+            metadata: notarization.metadata,
+        })
+    }
+}
+
+/// Builder of verify_notarization API
+pub struct VerifyNotarizationBuilder<'a, R> {
+    client: &'a Client,
+    receipt: &'a NotarizationReceipt,
+    reader: R,
+    check_inclusion: bool,
+}
+
+impl<'a, R: Read + Send + 'static> VerifyNotarizationBuilder<'a, R> {
+    /// Create verify_notarization builder
+    pub fn new(client: &'a Client, receipt: &'a NotarizationReceipt, reader: R) -> Self {
+        Self {
+            client,
+            receipt,
+            reader,
+            check_inclusion: false,
+        }
+    }
+
+    /// Also check that the notarization message is referenced by a milestone, and get the milestone timestamp
+    pub fn check_inclusion(mut self, check_inclusion: bool) -> Self {
+        self.check_inclusion = check_inclusion;
+        self
+    }
+
+    /// Consume the builder and get the API result
+    pub async fn get(self) -> Result<NotarizationVerification> {
+        if self.receipt.algorithm != NOTARIZATION_ALGORITHM {
+            return Err(Error::InvalidParameter(format!(
+                "unsupported hash algorithm {}",
+                self.receipt.algorithm
+            )));
+        }
+        let hash_matches = hex::encode(hash_document(self.reader).await?) == self.receipt.hash;
+
+        let message_id = MessageId::from_str(&self.receipt.message_id)
+            .map_err(|_| Error::InvalidParameter(format!("message id {}", self.receipt.message_id)))?;
+        let message = self.client.get_message().data(&message_id).await?;
+        let message_matches = match message.payload() {
+            Some(Payload::Indexation(indexation)) if indexation.index() == self.receipt.index.as_str() => {
+                match serde_json::from_slice::<Notarization>(indexation.data()) {
+                    Ok(notarization) => {
+                        notarization.algorithm == self.receipt.algorithm
+                            && notarization.hash == self.receipt.hash
+                            && notarization.timestamp == self.receipt.timestamp
+                            && notarization.metadata == self.receipt.metadata
+                    }
+                    Err(_) => false,
+                }
+            }
+            _ => false,
+        };
+
+        let (mut milestone_index, mut milestone_timestamp) = (None, None);
+        if self.check_inclusion {
+            let metadata = self.client.get_message().metadata(&message_id).await?;
+            if let Some(index) = metadata.referenced_by_milestone_index {
+                let milestone = self.client.get_milestone(index).await?;
+                milestone_index = Some(index);
+                milestone_timestamp = Some(milestone.timestamp);
+            }
+        }
+
+        Ok(NotarizationVerification {
+            hash_matches,
+            message_matches,
+            milestone_index,
+            milestone_timestamp,
+        })
+    }
+}
+
+/// Hash the document on a blocking thread, since reading it may block.
+async fn hash_document<R: Read + Send + 'static>(mut reader: R) -> Result<[u8; 32]> {
+    let hash = tokio::task::spawn_blocking(move || hash_reader(&mut reader))
+        .await
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))??;
+    Ok(hash)
+}
@@ -1,47 +1,104 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Client, ClientMiner, Error, Result};
+use crate::{
+    api::{
+        child_path, derive_addresses, discover_chain, sign_transaction, Account, AddressPath, FirstFit, InputSelection,
+        PreparedInput, PreparedOutput, PreparedTransaction, DEFAULT_GAP_LIMIT, INPUT_COUNT_MAX, OUTPUT_COUNT_MAX,
+    },
+    encryption,
+    signing::Signer,
+    types::OutputMetadata,
+    Bech32Address, Client, Error, Result,
+};
 
-use bee_common::packable::Packable;
 use bee_message::prelude::*;
-use bee_signing_ext::{
-    binary::{BIP32Path, Ed25519PrivateKey},
-    Seed, Signer,
-};
-use std::{collections::HashMap, convert::TryInto, num::NonZeroU64};
+use bee_signing_ext::binary::BIP32Path;
+use std::{collections::HashSet, convert::TryInto, num::NonZeroU64, ops::Range};
 
-const HARDEND: u32 = 1 << 31;
 const TRANSACTION_ID_LENGTH: usize = 32;
 
+/// Where the remaining tokens of the inputs are sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemainderStrategy {
+    /// Send the remainder back to the address of the last input. This reuses the address, so it isn't recommended.
+    ReuseAddress,
+    /// Send the remainder to the first unused address of the searched path, or of the external chain of the account
+    NextUnusedAddress,
+    /// Send the remainder to the first unused address of the internal chain of the account
+    ChangeAddress,
+    /// Send the remainder to the given address. It's listed as a regular output of the prepared transaction, since it
+    /// can't be checked to belong to the seed.
+    Address(Address),
+}
+
 /// Builder of send API
 pub struct SendBuilder<'a> {
     client: &'a Client,
-    seed: &'a Seed,
+    signer: Option<&'a dyn Signer>,
+    address_paths: Option<&'a [AddressPath]>,
     path: Option<&'a BIP32Path>,
+    account: Option<Account>,
     index: Option<usize>,
     outputs: Vec<Output>,
     indexation: Option<Indexation>,
+    recipients: Vec<encryption::PublicKey>,
+    remainder: Option<RemainderStrategy>,
+    input_selection: Box<dyn InputSelection + 'a>,
+    inputs: Vec<UTXOInput>,
+    input_range: Range<usize>,
+    gap_limit: usize,
+    min_remainder: u64,
 }
 
-/// Structure for sorting of UnlockBlocks
-// TODO: move the sorting process to the `Message` crate
-struct AddressIndexRecorder {
-    input: Input,
-    address_index: usize,
-    address_path: BIP32Path,
+/// Unspent outputs of the seed which can be used as inputs, with their amount and address, and the first unused
+/// addresses of the external and of the internal chain found while searching them, with their path
+#[derive(Default)]
+struct InputCandidates {
+    candidates: Vec<(AddressIndexRecorder, u64, Address)>,
+    unused_address: Option<(Address, BIP32Path)>,
+    change_address: Option<(Address, BIP32Path)>,
+}
+
+/// Input spending an output of the seed, with the path of its address
+pub(crate) struct AddressIndexRecorder {
+    pub(crate) input: Input,
+    pub(crate) address_path: BIP32Path,
 }
 
 impl<'a> SendBuilder<'a> {
     /// Create sned builder
-    pub fn new(client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
+        Self::with_source(client, Some(signer), None)
+    }
+
+    /// Create the builder of a transaction spending from the given addresses of a seed, without the seed. The
+    /// transaction can only be prepared, to be signed by the holder of the seed.
+    pub fn with_address_paths(client: &'a Client, address_paths: &'a [AddressPath]) -> Self {
+        Self::with_source(client, None, Some(address_paths))
+    }
+
+    fn with_source(
+        client: &'a Client,
+        signer: Option<&'a dyn Signer>,
+        address_paths: Option<&'a [AddressPath]>,
+    ) -> Self {
         Self {
             client,
-            seed,
+            signer,
+            address_paths,
             path: None,
+            account: None,
             index: None,
             outputs: Vec::new(),
             indexation: None,
+            recipients: Vec::new(),
+            remainder: None,
+            input_selection: Box::new(FirstFit),
+            inputs: Vec::new(),
+            input_range: 0..100,
+            gap_limit: DEFAULT_GAP_LIMIT,
+            min_remainder: 0,
         }
     }
 
@@ -51,6 +108,13 @@ impl<'a> SendBuilder<'a> {
         self
     }
 
+    /// Set the account to spend from, instead of the path. The inputs are searched on both chains of the account and
+    /// the remainder is sent to an unused address of its internal chain by default.
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
     /// Set index to the builder
     pub fn index(mut self, index: usize) -> Self {
         self.index = Some(index);
@@ -70,14 +134,103 @@ impl<'a> SendBuilder<'a> {
         self
     }
 
-    /// Consume the builder and get the API result
-    pub async fn post(self) -> Result<MessageId> {
-        let path = match self.path {
-            Some(p) => p,
-            None => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+    /// Encrypt the data of the indexation payload for the given recipients, so only they can read it.
+    pub fn encryption_recipients(mut self, recipients: &[encryption::PublicKey]) -> Self {
+        self.recipients = recipients.to_vec();
+        self
+    }
+
+    /// Set where the remainder is sent. Defaults to [`RemainderStrategy::ChangeAddress`] with an account and to
+    /// [`RemainderStrategy::NextUnusedAddress`] otherwise.
+    pub fn remainder(mut self, strategy: RemainderStrategy) -> Self {
+        self.remainder = Some(strategy);
+        self
+    }
+
+    /// Set the strategy selecting the inputs among the unspent outputs of the seed. Defaults to [`FirstFit`].
+    pub fn input_selection<S: InputSelection + 'a>(mut self, strategy: S) -> Self {
+        self.input_selection = Box::new(strategy);
+        self
+    }
+
+    /// Spend the given input instead of discovering the unspent outputs of the seed. All the given inputs are spent,
+    /// and an input given more than once is rejected.
+    pub fn input(mut self, input: UTXOInput) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Spend the given inputs instead of discovering the unspent outputs of the seed. All the given inputs are spent,
+    /// and an input given more than once is rejected.
+    pub fn inputs(mut self, inputs: Vec<UTXOInput>) -> Self {
+        self.inputs.extend(inputs);
+        self
+    }
+
+    /// Set the range of address indexes searched to check that the given inputs belong to the seed, and for an unused
+    /// address to send the remainder to when the inputs are given. Defaults to `0..100`.
+    pub fn input_range(mut self, range: Range<usize>) -> Self {
+        self.input_range = range;
+        self
+    }
+
+    /// Set the number of consecutive addresses which never received any output after which the search of the inputs
+    /// stops. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Select inputs covering this amount on top of the outputs, so it's left in the remainder
+    pub(crate) fn min_remainder(mut self, amount: u64) -> Self {
+        self.min_remainder = amount;
+        self
+    }
+
+    /// Consume the builder and get the API result, the message id and the address the remainder was sent to, if any
+    pub async fn post(self) -> Result<(MessageId, Option<Address>)> {
+        let client = self.client;
+        let signer = self.signer()?;
+        let (prepared, remainder_address) = self.build().await?;
+        let signed = sign_transaction(prepared, signer).await?;
+        let message_id = client.submit_transaction(signed).await?;
+
+        Ok((message_id, remainder_address))
+    }
+
+    /// Consume the builder and get the unsigned transaction, searching the inputs and building the outputs without
+    /// signing. Sign it with [`sign_transaction`], possibly on an offline machine, then post it with
+    /// `Client::submit_transaction`.
+    pub async fn prepare(self) -> Result<PreparedTransaction> {
+        Ok(self.build().await?.0)
+    }
+
+    /// Build the unsigned transaction, and get the address the remainder is sent to, if any
+    async fn build(self) -> Result<(PreparedTransaction, Option<Address>)> {
+        // The chains to search the inputs on, with whether they are internal, unless the addresses are given
+        let known = self.known_addresses()?;
+        let chains = match (&known, self.account, self.path) {
+            (Some(_), Some(_), _) => {
+                return Err(Error::InvalidParameter(String::from(
+                    "account can't be combined with address paths",
+                )))
+            }
+            (Some(_), None, _) => Vec::new(),
+            (None, Some(account), _) => vec![(account.chain_path(false), false), (account.chain_path(true), true)],
+            (None, None, Some(p)) => vec![(p.clone(), false)],
+            (None, None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
         };
 
-        let mut index = match self.index {
+        let remainder_strategy = match (self.remainder.clone(), self.account) {
+            (Some(RemainderStrategy::ChangeAddress), None) => {
+                return Err(Error::MissingParameter(String::from("account")))
+            }
+            (Some(strategy), _) => strategy,
+            (None, Some(_)) => RemainderStrategy::ChangeAddress,
+            (None, None) => RemainderStrategy::NextUnusedAddress,
+        };
+
+        let index = match self.index {
             Some(r) => r,
             None => 0,
         };
@@ -86,168 +239,328 @@ impl<'a> SendBuilder<'a> {
             return Err(Error::MissingParameter(String::from("Outputs")));
         }
 
+        if self.gap_limit == 0 {
+            return Err(Error::InvalidParameter(String::from("gap limit must be at least 1")));
+        }
+
+        if self.outputs.len() > OUTPUT_COUNT_MAX {
+            return Err(Error::TooManyOutputs(self.outputs.len(), OUTPUT_COUNT_MAX));
+        }
+
         // Calculate the total tokens to spend
         let mut total_to_spend = 0;
-        let mut total_already_spent = 0;
         for output in &self.outputs {
             if let Output::SignatureLockedSingle(x) = &output {
                 total_to_spend += x.amount().get();
             }
         }
 
-        let mut paths = Vec::new();
-        let mut essence = TransactionEssence::builder();
-        let mut empty_address_count: u32 = 0;
-        let mut address_index_recorders = Vec::new();
-
-        // The gap limit is 20
-        while empty_address_count != 20 {
-            // Reset the empty_address_count for each run of output address searching
-            empty_address_count = 0;
-
-            // Get the addresses in the BIP path/index ~ path/index+20
-            let addresses = self
-                .client
-                .find_addresses(self.seed)
-                .path(path)
-                .range(index..index + 20)
-                .get()?;
-
-            // For each address, get the address outputs
-            for (address_index, address) in addresses.iter().enumerate() {
-                let address_outputs = self.client.get_address().outputs(&address).await?;
-                let mut outputs = vec![];
-                for output_id in address_outputs.iter() {
-                    let curr_outputs = self.client.get_output(output_id).await?;
-                    outputs.push(curr_outputs);
-                }
+        let InputCandidates {
+            candidates,
+            unused_address,
+            change_address,
+        } = match (self.inputs.is_empty(), &known) {
+            (true, Some(known)) => self.find_known_inputs(known).await?,
+            (true, None) => self.discover_inputs(&chains, index).await?,
+            (false, _) => self.find_inputs(&chains, known.as_deref()).await?,
+        };
 
-                // If there are more than 20 (gap limit) consecutive empty addresses, then we stop looking
-                // up the addresses belonging to the seed. Note that we don't really count the exact 20
-                // consecutive empty addresses, which is uncessary. We just need to check the address range,
-                // [k*20, k*20 + 20), where k is natural number, and to see if the outpus are all empty.
-                if outputs.is_empty() {
-                    // Accumulate the empty_address_count for each run of output address searching
-                    empty_address_count += 1;
+        // Select the inputs among the unspent outputs, the given inputs are all used
+        let amounts: Vec<u64> = candidates.iter().map(|(_, amount, _)| *amount).collect();
+        let mut selection = match self.inputs.is_empty() {
+            true => self
+                .input_selection
+                .select(&amounts, total_to_spend + self.min_remainder, INPUT_COUNT_MAX)?,
+            false => {
+                if candidates.len() > INPUT_COUNT_MAX {
+                    return Err(Error::TooManyInputs(INPUT_COUNT_MAX));
                 }
+                (0..candidates.len()).collect()
+            }
+        };
+        selection.sort_unstable();
 
-                for (_offset, output) in outputs.into_iter().enumerate() {
-                    match output.is_spent {
-                        true => {
-                            if output.amount != 0 {
-                                return Err(Error::SpentAddress);
-                            }
-                        }
-                        false => {
-                            if output.amount != 0 && total_already_spent < total_to_spend {
-                                total_already_spent += output.amount;
-                                let mut address_path = path.clone();
-                                // Note that we need to sign the original address, i.e., `path/index`,
-                                // instead of `path/index/_offset` or `path/_offset`.
-                                address_path.push(address_index as u32 + HARDEND);
-                                let transaction_id: [u8; TRANSACTION_ID_LENGTH] = output.transaction_id[..]
-                                    .try_into()
-                                    .map_err(|_| Error::TransactionError)?;
-                                paths.push(address_path.clone());
-                                let input = Input::UTXO(
-                                    UTXOInput::new(TransactionId::from(transaction_id), output.output_index)
-                                        .map_err(|_| Error::TransactionError)?,
-                                );
-                                essence = essence.add_input(input.clone());
-                                address_index_recorders.push(AddressIndexRecorder {
-                                    input,
-                                    address_index,
-                                    address_path,
-                                });
-                                // Output the remaining tokens back to the original address
-                                if total_already_spent > total_to_spend {
-                                    essence = essence.add_output(
-                                        SignatureLockedSingleOutput::new(
-                                            address.clone(),
-                                            NonZeroU64::new(total_already_spent - total_to_spend).unwrap(),
-                                        )
-                                        .into(),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
+        let mut essence = TransactionEssence::builder();
+        let mut total_already_spent = 0;
+        let mut prepared_inputs = Vec::new();
+        let mut prepared_outputs = Vec::new();
+        // Address of the last selected input, with its path
+        let mut input_address = None;
+        let mut selected = selection.into_iter().peekable();
+        for (candidate_index, (recorder, amount, address)) in candidates.into_iter().enumerate() {
+            if selected.peek() != Some(&candidate_index) {
+                continue;
             }
-            index += 20;
+            selected.next();
+            total_already_spent += amount;
+            let output_id = match &recorder.input {
+                Input::UTXO(input) => input.output_id().to_string(),
+                _ => return Err(Error::TransactionError),
+            };
+            prepared_inputs.push(PreparedInput {
+                output_id,
+                address: address.to_bech32(),
+                amount,
+                path: recorder.address_path.to_string(),
+            });
+            essence = essence.add_input(recorder.input);
+            input_address = Some((address, recorder.address_path));
         }
 
-        if total_already_spent < total_to_spend {
-            return Err(Error::NotEnoughBalance(total_to_spend));
+        if total_already_spent < total_to_spend + self.min_remainder {
+            return Err(Error::NotEnoughBalance(total_already_spent));
+        }
+        if total_already_spent > total_to_spend && self.outputs.len() == OUTPUT_COUNT_MAX {
+            return Err(Error::TooManyOutputs(OUTPUT_COUNT_MAX + 1, OUTPUT_COUNT_MAX));
+        }
+
+        // Output the remaining tokens to the address chosen by the remainder strategy. The path of the address is
+        // known unless the address was given.
+        let mut remainder_address = None;
+        if total_already_spent > total_to_spend {
+            let (address, path) = match (remainder_strategy, unused_address, change_address) {
+                (RemainderStrategy::Address(address), _, _) => (address, None),
+                (strategy, unused_address, change_address) => {
+                    let (address, path) = match (strategy, unused_address, change_address) {
+                        (RemainderStrategy::NextUnusedAddress, Some(address), _) => address,
+                        (RemainderStrategy::NextUnusedAddress, None, _) => match &known {
+                            Some(known) => self.find_unused_known_address(known).await?,
+                            None => self.find_unused_address(&chains[0].0).await?,
+                        },
+                        (RemainderStrategy::ChangeAddress, _, Some(address)) => address,
+                        (RemainderStrategy::ChangeAddress, _, None) => self.find_unused_address(&chains[1].0).await?,
+                        // RemainderStrategy::ReuseAddress
+                        _ => input_address.ok_or(Error::TransactionError)?,
+                    };
+                    (address, Some(path))
+                }
+            };
+            essence = essence.add_output(
+                SignatureLockedSingleOutput::new(
+                    address.clone(),
+                    NonZeroU64::new(total_already_spent - total_to_spend).unwrap(),
+                )
+                .into(),
+            );
+            prepared_outputs.push(PreparedOutput {
+                address: address.to_bech32(),
+                amount: total_already_spent - total_to_spend,
+                remainder: path.is_some(),
+                path: path.map(|path| path.to_string()),
+            });
+            remainder_address = Some(address);
         }
 
-        // Build signed transaction payload
+        // Build the essence of the transaction
         let outputs = self.outputs;
         for output in outputs {
+            if let Output::SignatureLockedSingle(x) = &output {
+                prepared_outputs.push(PreparedOutput {
+                    address: x.address().to_bech32(),
+                    amount: x.amount().get(),
+                    remainder: false,
+                    path: None,
+                });
+            }
             essence = essence.add_output(output);
         }
-        if let Some(indexation_payload) = self.indexation {
+        if let Some(mut indexation_payload) = self.indexation {
+            if !self.recipients.is_empty() {
+                indexation_payload = encryption::encrypt_indexation(
+                    indexation_payload.index().to_string(),
+                    indexation_payload.data(),
+                    &self.recipients,
+                )?;
+            }
             essence = essence.with_payload(Payload::Indexation(Box::new(indexation_payload)))
         }
         let essence = essence.finish()?;
-        let mut serialized_essence = Vec::new();
-        essence
-            .pack(&mut serialized_essence)
-            .map_err(|_| Error::InvalidParameter("inputs".to_string()))?;
-
-        let mut unlock_blocks = Vec::new();
-        let mut current_block_index: usize = 0;
-        let mut signature_indexes = HashMap::<usize, usize>::new();
-        address_index_recorders.sort_by(|a, b| a.input.cmp(&b.input));
-
-        for recorder in address_index_recorders.iter() {
-            // Check if current path is same as previous path
-            // If so, add a reference unlock block
-            if let Some(block_index) = signature_indexes.get(&recorder.address_index) {
-                unlock_blocks.push(UnlockBlock::Reference(ReferenceUnlock::new(*block_index as u16)?));
-            } else {
-                // If not, we should create a signature unlock block
-                match &self.seed {
-                    Seed::Ed25519(s) => {
-                        let private_key = Ed25519PrivateKey::generate_from_seed(s, &recorder.address_path)
-                            .map_err(|_| Error::InvalidParameter("seed inputs".to_string()))?;
-                        let public_key = private_key.generate_public_key().to_bytes();
-                        // The block should sign the entire transaction essence part of the transaction payload
-                        let signature = Box::new(private_key.sign(&serialized_essence).to_bytes());
-                        unlock_blocks.push(UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
-                            public_key, signature,
-                        ))));
+
+        let prepared = PreparedTransaction {
+            essence: (&essence).into(),
+            inputs: prepared_inputs,
+            outputs: prepared_outputs,
+        };
+
+        Ok((prepared, remainder_address))
+    }
+
+    /// Search the unspent outputs of the seed on the chains, from the given address index until the gap limit.
+    async fn discover_inputs(&self, chains: &[(BIP32Path, bool)], index: usize) -> Result<InputCandidates> {
+        let mut found = InputCandidates::default();
+
+        for (path, internal) in chains {
+            let chain = discover_chain(self.client, self.signer()?, path, *internal, index, self.gap_limit).await?;
+            let (unused_index, unused_address) = chain.unused_address;
+            let unused_address = Some((unused_address, child_path(path, unused_index)));
+            match internal {
+                true => found.change_address = unused_address,
+                false => found.unused_address = unused_address,
+            }
+
+            for used in chain.used.into_iter().filter(|used| used.balance != 0) {
+                for output_id in used.outputs.iter() {
+                    let output = self.client.get_output(output_id).await?;
+                    // Spent outputs are part of the history of the address, but can't be used as inputs
+                    if !output.is_spent && output.amount != 0 {
+                        let recorder = input_recorder(&output, child_path(path, used.index))?;
+                        found.candidates.push((recorder, output.amount, used.address.clone()));
                     }
-                    Seed::Wots(_) => panic!("Wots signing scheme isn't supported."),
                 }
-                signature_indexes.insert(recorder.address_index, current_block_index);
+            }
+        }
 
-                // Update current block index
-                current_block_index += 1;
+        Ok(found)
+    }
+
+    /// Get the unspent outputs of the given addresses.
+    async fn find_known_inputs(&self, known: &[(Address, BIP32Path)]) -> Result<InputCandidates> {
+        let mut found = InputCandidates::default();
+        for (address, path) in known {
+            for output_id in self.client.get_address().outputs(address).await?.iter() {
+                let output = self.client.get_output(output_id).await?;
+                if !output.is_spent && output.amount != 0 {
+                    let recorder = input_recorder(&output, path.clone())?;
+                    found.candidates.push((recorder, output.amount, address.clone()));
+                }
             }
         }
-        // TODO overflow check
-        let mut payload_builder = TransactionBuilder::new().with_essence(essence);
-        for unlock in unlock_blocks {
-            payload_builder = payload_builder.add_unlock_block(unlock);
+
+        Ok(found)
+    }
+
+    /// Get the outputs of the given inputs, checking that they are unspent and that their address belongs to the seed,
+    /// either as one of the given addresses or by deriving the addresses of the input range on the chains.
+    async fn find_inputs(
+        &self,
+        chains: &[(BIP32Path, bool)],
+        known: Option<&[(Address, BIP32Path)]>,
+    ) -> Result<InputCandidates> {
+        let mut output_ids = HashSet::new();
+        for input in &self.inputs {
+            let output_id = input.output_id().to_string();
+            if !output_ids.insert(output_id.clone()) {
+                return Err(Error::InvalidParameter(format!(
+                    "input {} is given more than once",
+                    output_id
+                )));
+            }
+        }
+
+        let owners = match known {
+            Some(known) => known.to_vec(),
+            None => {
+                let mut owners = Vec::new();
+                for (path, _) in chains {
+                    let addresses =
+                        derive_addresses(self.client, self.signer()?, path, self.input_range.clone()).await?;
+                    owners.extend(
+                        addresses
+                            .into_iter()
+                            .zip(self.input_range.clone())
+                            .map(|(address, index)| (address, child_path(path, index))),
+                    );
+                }
+                owners
+            }
+        };
+
+        let mut found = InputCandidates::default();
+        for input in &self.inputs {
+            let output = self.client.get_output(input).await?;
+            let output_id = input.output_id().to_string();
+            if output.is_spent {
+                return Err(Error::InvalidParameter(format!("input {} is already spent", output_id)));
+            }
+
+            let (_, path) = owners
+                .iter()
+                .find(|(address, _)| *address == output.address)
+                .ok_or(Error::InputAddressNotFound(output_id))?;
+            let recorder = input_recorder(&output, path.clone())?;
+            found.candidates.push((recorder, output.amount, output.address.clone()));
         }
 
-        let payload = payload_builder.finish().map_err(|_| Error::TransactionError)?;
+        Ok(found)
+    }
+
+    /// Get the given addresses parsed, if any
+    fn known_addresses(&self) -> Result<Option<Vec<(Address, BIP32Path)>>> {
+        self.address_paths
+            .map(|address_paths| {
+                address_paths
+                    .iter()
+                    .map(|address_path| {
+                        let path = BIP32Path::from_str(&address_path.path)
+                            .map_err(|_| Error::InvalidParameter(format!("BIP32 path {}", address_path.path)))?;
+                        Ok((Address::try_from_bech32(&address_path.address)?, path))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Get the signer, which is missing when the builder was created from address paths
+    fn signer(&self) -> Result<&'a dyn Signer> {
+        self.signer
+            .ok_or_else(|| Error::MissingParameter(String::from("signer")))
+    }
 
-        // get tips
-        let tips = self.client.get_tips().await?;
+    /// Whether the address never received any output
+    async fn is_unused(&self, address: &Address) -> Result<bool> {
+        Ok(self
+            .client
+            .get_address()
+            .include_spent(true)
+            .outputs(address)
+            .await?
+            .is_empty())
+    }
 
-        // building message
-        let payload = Payload::Transaction(Box::new(payload));
-        let message = MessageBuilder::<ClientMiner>::new()
-            .with_network_id(self.client.get_network_id().await?)
-            .with_parent1(tips.0)
-            .with_parent2(tips.1)
-            .with_payload(payload)
-            .with_nonce_provider(self.client.get_pow_provider(), 4000f64)
-            .finish()
-            .map_err(|_| Error::TransactionError)?;
+    /// Get the first of the given addresses which never received any output.
+    async fn find_unused_known_address(&self, known: &[(Address, BIP32Path)]) -> Result<(Address, BIP32Path)> {
+        for (address, path) in known {
+            if self.is_unused(address).await? {
+                return Ok((address.clone(), path.clone()));
+            }
+        }
+        Err(Error::UnusedAddressNotFound(format!("0..{}", known.len())))
+    }
 
-        self.client.post_message(&message).await
+    /// Get the first address which never received any output on the chain, with its path, searching until the end of
+    /// the input range, or at least the gap limit.
+    async fn find_unused_address(&self, path: &BIP32Path) -> Result<(Address, BIP32Path)> {
+        let start = self.index.unwrap_or(0);
+        let end = self.input_range.end.max(start + self.gap_limit);
+        let mut index = start;
+        while index < end {
+            let range_end = end.min(index + self.gap_limit);
+            let addresses = self
+                .client
+                .find_addresses(self.signer()?)
+                .path(path)
+                .range(index..range_end)
+                .get()
+                .await?;
+            for (address, address_index) in addresses.into_iter().zip(index..range_end) {
+                if self.is_unused(&address).await? {
+                    return Ok((address, child_path(path, address_index)));
+                }
+            }
+            index = range_end;
+        }
+        Err(Error::UnusedAddressNotFound(format!("{}..{}", start, end)))
     }
 }
+
+/// Build the recorder of the input spending the output of the address with the given path.
+pub(crate) fn input_recorder(output: &OutputMetadata, address_path: BIP32Path) -> Result<AddressIndexRecorder> {
+    let transaction_id: [u8; TRANSACTION_ID_LENGTH] = output.transaction_id[..]
+        .try_into()
+        .map_err(|_| Error::TransactionError)?;
+    let input = Input::UTXO(
+        UTXOInput::new(TransactionId::from(transaction_id), output.output_index)
+            .map_err(|_| Error::TransactionError)?,
+    );
+    Ok(AddressIndexRecorder { input, address_path })
+}
@@ -0,0 +1,218 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    api::{child_path, derive_address_of_path},
+    client::hash_data,
+    signing::Signer,
+    types::{PayloadJson, TransactionEssenceJson},
+    Error, Result,
+};
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_signing_ext::binary::BIP32Path;
+
+use std::{collections::HashMap, convert::TryFrom, ops::Range};
+
+/// An address of the seed with the BIP32 path of its key. The addresses are exported with [`derive_address_paths`]
+/// from the offline machine holding the seed, so the online one can prepare transactions with
+/// `Client::prepare_transaction` without the seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressPath {
+    /// Bech32 encoded address
+    pub address: String,
+    /// BIP32 path of the address
+    pub path: String,
+}
+
+/// Derive the addresses `path/index'` of the indexes in the range, with their path.
+pub async fn derive_address_paths(
+    signer: &dyn Signer,
+    path: &BIP32Path,
+    range: Range<usize>,
+) -> Result<Vec<AddressPath>> {
+    let mut address_paths = Vec::new();
+    for index in range {
+        let address_path = child_path(path, index);
+        address_paths.push(AddressPath {
+            address: derive_address_of_path(signer, &address_path).await?.to_bech32(),
+            path: address_path.to_string(),
+        });
+    }
+    Ok(address_paths)
+}
+
+/// An unsigned transaction returned by `SendBuilder::prepare`. It's serializable, so it can be signed with
+/// [`sign_transaction`] on an offline machine holding the seed, and then submitted by an online one with
+/// `Client::submit_transaction`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreparedTransaction {
+    /// Essence of the transaction, which is signed
+    pub essence: TransactionEssenceJson,
+    /// Inputs of the essence, with the path of their address
+    pub inputs: Vec<PreparedInput>,
+    /// Outputs of the essence
+    pub outputs: Vec<PreparedOutput>,
+}
+
+/// An input of a prepared transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreparedInput {
+    /// Output id of the input, the hex encoded transaction id and output index
+    #[serde(rename = "outputId")]
+    pub output_id: String,
+    /// Bech32 encoded address of the input
+    pub address: String,
+    /// Amount of the input
+    pub amount: u64,
+    /// BIP32 path of the address, to derive the key signing the input
+    pub path: String,
+}
+
+/// An output of a prepared transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreparedOutput {
+    /// Bech32 encoded address of the output
+    pub address: String,
+    /// Amount of the output
+    pub amount: u64,
+    /// Whether the output is the remainder of the inputs, sent back to an address of the seed
+    pub remainder: bool,
+    /// BIP32 path of the address of the remainder, checked when signing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// A transaction signed with [`sign_transaction`], ready to be submitted with `Client::submit_transaction`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    /// The transaction payload
+    pub payload: PayloadJson,
+}
+
+/// Sign the prepared transaction with the signer, e.g. a seed. This doesn't need a node, so it can run on an offline
+/// machine. The inputs and outputs listed in the prepared transaction are checked against its essence, and the
+/// addresses of the inputs and of the remainders are derived from their path, so what's reviewed is what's signed.
+pub async fn sign_transaction(prepared: PreparedTransaction, signer: &dyn Signer) -> Result<SignedTransaction> {
+    let essence = TransactionEssence::try_from(prepared.essence)?;
+
+    // The inputs must be spent with the keys of their address, and the remainders must stay on the seed
+    for input in &prepared.inputs {
+        check_address_path(signer, &input.address, &input.path).await?;
+    }
+    for output in prepared.outputs.iter().filter(|output| output.remainder) {
+        let path = output
+            .path
+            .as_ref()
+            .ok_or_else(|| Error::InvalidParameter(format!("the remainder to {} has no BIP32 path", output.address)))?;
+        check_address_path(signer, &output.address, path).await?;
+    }
+
+    // Check the listings, since they're what the user reviews before signing
+    let mut outputs: Vec<(String, u64)> = Vec::new();
+    for output in essence.outputs() {
+        match output {
+            Output::SignatureLockedSingle(output) => {
+                outputs.push((output.address().to_bech32(), output.amount().get()))
+            }
+            _ => return Err(Error::InvalidParameter(String::from("output type"))),
+        }
+    }
+    let mut listed_outputs: Vec<(String, u64)> = prepared
+        .outputs
+        .iter()
+        .map(|output| (output.address.clone(), output.amount))
+        .collect();
+    outputs.sort();
+    listed_outputs.sort();
+    if outputs != listed_outputs {
+        return Err(Error::InvalidParameter(String::from(
+            "the outputs of the prepared transaction don't match its essence",
+        )));
+    }
+    if essence.inputs().len() != prepared.inputs.len() {
+        return Err(Error::InvalidParameter(String::from(
+            "the inputs of the prepared transaction don't match its essence",
+        )));
+    }
+    let inputs: HashMap<&str, &PreparedInput> = prepared
+        .inputs
+        .iter()
+        .map(|input| (input.output_id.as_str(), input))
+        .collect();
+
+    let mut serialized_essence = Vec::new();
+    essence
+        .pack(&mut serialized_essence)
+        .map_err(|_| Error::InvalidParameter("inputs".to_string()))?;
+
+    let mut unlock_blocks = Vec::new();
+    let mut current_block_index: usize = 0;
+    let mut signature_indexes = HashMap::<&str, usize>::new();
+
+    // The unlock blocks follow the order of the inputs in the essence
+    for input in essence.inputs() {
+        let output_id = match input {
+            Input::UTXO(input) => input.output_id().to_string(),
+            _ => return Err(Error::InvalidParameter(String::from("input type"))),
+        };
+        let prepared_input = inputs.get(output_id.as_str()).ok_or_else(|| {
+            Error::InvalidParameter(String::from(
+                "the inputs of the prepared transaction don't match its essence",
+            ))
+        })?;
+
+        // Check if the address of the input was already signed
+        // If so, add a reference unlock block
+        if let Some(block_index) = signature_indexes.get(prepared_input.path.as_str()) {
+            unlock_blocks.push(UnlockBlock::Reference(ReferenceUnlock::new(*block_index as u16)?));
+        } else {
+            // If not, we should create a signature unlock block
+            let path = BIP32Path::from_str(&prepared_input.path)
+                .map_err(|_| Error::InvalidParameter(format!("BIP32 path {}", prepared_input.path)))?;
+            let public_key = signer.public_key(&path).await?;
+            // The block should sign the entire transaction essence part of the transaction payload
+            let signature = Box::new(signer.sign(&path, &serialized_essence).await?);
+            unlock_blocks.push(UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+                public_key, signature,
+            ))));
+            signature_indexes.insert(prepared_input.path.as_str(), current_block_index);
+
+            // Update current block index
+            current_block_index += 1;
+        }
+    }
+
+    let mut payload_builder = Transaction::builder().with_essence(essence);
+    for unlock in unlock_blocks {
+        payload_builder = payload_builder.add_unlock_block(unlock);
+    }
+    let payload = payload_builder.finish().map_err(|_| Error::TransactionError)?;
+
+    Ok(SignedTransaction {
+        payload: (&Payload::Transaction(Box::new(payload))).into(),
+    })
+}
+
+/// Compute the id of a transaction payload, the BLAKE2b-256 hash of its serialization, which identifies its outputs
+/// whichever message it's attached to.
+pub(crate) fn transaction_id(payload: &Payload) -> Result<TransactionId> {
+    let mut serialized_payload = Vec::new();
+    payload
+        .pack(&mut serialized_payload)
+        .map_err(|_| Error::InvalidParameter("transaction payload".to_string()))?;
+    Ok(TransactionId::new(hash_data(&serialized_payload)))
+}
+
+/// Check that the bech32 encoded address is the one of the key with the given path.
+async fn check_address_path(signer: &dyn Signer, address: &str, path: &str) -> Result<()> {
+    let bip32_path = BIP32Path::from_str(path).map_err(|_| Error::InvalidParameter(format!("BIP32 path {}", path)))?;
+    if derive_address_of_path(signer, &bip32_path).await?.to_bech32() != address {
+        return Err(Error::InvalidParameter(format!(
+            "the address {} doesn't belong to the BIP32 path {}",
+            address, path
+        )));
+    }
+    Ok(())
+}
@@ -1,27 +1,40 @@
 // Copyright 2020 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Client, Error, Result};
+use crate::{
+    api::{Account, DEFAULT_GAP_LIMIT},
+    signing::Signer,
+    Client, Error, Result,
+};
 
-use bee_message::prelude::Address;
-use bee_signing_ext::{binary::BIP32Path, Seed};
+use bee_message::prelude::{Address, UTXOInput};
+use bee_signing_ext::binary::BIP32Path;
+use futures::future::try_join_all;
 
 /// Builder of get_unspent_address API
 pub struct GetUnspentAddressBuilder<'a> {
     client: &'a Client,
-    seed: &'a Seed,
+    signer: &'a dyn Signer,
     path: Option<&'a BIP32Path>,
+    account: Option<Account>,
     index: Option<usize>,
+    gap_limit: usize,
+    allow_received: bool,
+    reserve: bool,
 }
 
 impl<'a> GetUnspentAddressBuilder<'a> {
     /// Create get_unspent_address builder
-    pub fn new(client: &'a Client, seed: &'a Seed) -> Self {
+    pub fn new(client: &'a Client, signer: &'a dyn Signer) -> Self {
         Self {
             client,
-            seed,
+            signer,
             path: None,
+            account: None,
             index: None,
+            gap_limit: DEFAULT_GAP_LIMIT,
+            allow_received: false,
+            reserve: false,
         }
     }
 
@@ -31,50 +44,111 @@ impl<'a> GetUnspentAddressBuilder<'a> {
         self
     }
 
+    /// Set the account to get an address of its external chain, instead of the path
+    pub fn account(mut self, account: Account) -> Self {
+        self.account = Some(account);
+        self
+    }
+
     /// Set index to the builder
     pub fn index(mut self, index: usize) -> Self {
         self.index = Some(index);
         self
     }
 
+    /// Set the number of addresses derived and queried at once while searching the unspent address. Defaults to 20.
+    pub fn gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Also accept an address which received outputs but never spent any of them, checked with the spent flag of its
+    /// outputs. By default, only addresses which never received any output are returned.
+    pub fn allow_received(mut self, allow_received: bool) -> Self {
+        self.allow_received = allow_received;
+        self
+    }
+
+    /// Reserve the returned address in the client until it's released with `Client::release_address`, so concurrent
+    /// callers reserving addresses don't get the same one.
+    pub fn reserve(mut self, reserve: bool) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
     /// Consume the builder and get the API result
     pub async fn get(self) -> Result<(Address, usize)> {
-        let path = match self.path {
-            Some(p) => p,
-            None => return Err(Error::MissingParameter(String::from("BIP32 path"))),
+        let path = match (self.account, self.path) {
+            (Some(account), _) => account.chain_path(false),
+            (None, Some(p)) => p.clone(),
+            (None, None) => return Err(Error::MissingParameter(String::from("BIP32 path"))),
         };
+        if self.gap_limit == 0 {
+            return Err(Error::InvalidParameter(String::from("gap limit must be at least 1")));
+        }
 
         let mut index = match self.index {
             Some(r) => r,
             None => 0,
         };
 
-        let result = loop {
+        loop {
             let addresses = self
                 .client
-                .find_addresses(self.seed)
-                .path(path)
-                .range(index..index + 20)
-                .get()?;
-
-            // TODO we assume all addressees are unspent and valid if balance > 0
-            let mut address = None;
-            for a in addresses {
-                let address_balance = self.client.get_address().balance(&a).await?;
-                match address_balance {
-                    0 => {
-                        address = Some(a);
-                        break;
-                    }
-                    _ => index += 1,
+                .find_addresses(self.signer)
+                .path(&path)
+                .range(index..index + self.gap_limit)
+                .get()
+                .await?;
+
+            // An address which received and spent funds has no balance, so the whole output history is checked
+            let histories = try_join_all(
+                addresses
+                    .iter()
+                    .map(|address| self.client.get_address().include_spent(true).outputs(address)),
+            )
+            .await?;
+
+            for (offset, (address, history)) in addresses.into_iter().zip(histories.iter()).enumerate() {
+                if self.reserve
+                    && self
+                        .client
+                        .reserved_addresses
+                        .read()
+                        .unwrap()
+                        .contains(&address.to_bech32())
+                {
+                    continue;
                 }
+                if !self.is_unspent(history).await? {
+                    continue;
+                }
+                // Another caller may have reserved the address in the meantime
+                if self.reserve
+                    && !self
+                        .client
+                        .reserved_addresses
+                        .write()
+                        .unwrap()
+                        .insert(address.to_bech32())
+                {
+                    continue;
+                }
+                return Ok((address, index + offset));
             }
 
-            if let Some(a) = address {
-                break (a, index);
-            }
-        };
+            index += self.gap_limit;
+        }
+    }
 
-        Ok(result)
+    async fn is_unspent(&self, history: &[UTXOInput]) -> Result<bool> {
+        if history.is_empty() {
+            return Ok(true);
+        }
+        if !self.allow_received {
+            return Ok(false);
+        }
+        let outputs = try_join_all(history.iter().map(|output_id| self.client.get_output(output_id))).await?;
+        Ok(outputs.iter().all(|output| !output.is_spent))
     }
 }
@@ -4,8 +4,10 @@
 //! Builder of the Clinet Instnace
 
 use crate::{
+    api::{AddressCache, TIPS_REFRESH_INTERVAL},
     client::{BrokerOptions, Client},
     error::*,
+    pow::{NonceProvider, PayloadKind, PowProviderRegistry},
 };
 
 use reqwest::Url;
@@ -15,6 +17,7 @@ use std::{
     collections::HashSet,
     num::NonZeroU64,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 /// Network of the Iota nodes belong to
@@ -28,6 +31,21 @@ pub enum Network {
     Comnet,
 }
 
+impl Network {
+    /// All the networks
+    pub fn all() -> [Network; 3] {
+        [Network::Mainnet, Network::Devnet, Network::Comnet]
+    }
+
+    /// Bech32 HRPs of the addresses of the network
+    pub fn bech32_hrps(&self) -> &'static [&'static str] {
+        match self {
+            Network::Mainnet => &["iota", "iot"],
+            Network::Devnet | Network::Comnet => &["atoi", "toi"],
+        }
+    }
+}
+
 /// Builder to construct client instance with sensible default values
 pub struct ClientBuilder {
     nodes: Vec<Url>,
@@ -37,6 +55,12 @@ pub struct ClientBuilder {
     quorum_threshold: u8,
     broker_options: BrokerOptions,
     local_pow: bool,
+    min_pow_score: Option<f64>,
+    pow_worker_count: usize,
+    tips_interval: Duration,
+    pow_providers: PowProviderRegistry,
+    post_message_remote_pow_timeout: Duration,
+    address_cache_size: usize,
 }
 
 impl Default for ClientBuilder {
@@ -49,6 +73,12 @@ impl Default for ClientBuilder {
             quorum_threshold: 50,
             broker_options: Default::default(),
             local_pow: true,
+            min_pow_score: None,
+            pow_worker_count: num_cpus::get(),
+            tips_interval: TIPS_REFRESH_INTERVAL,
+            pow_providers: Default::default(),
+            post_message_remote_pow_timeout: Duration::from_secs(30),
+            address_cache_size: 100_000,
         }
     }
 }
@@ -115,6 +145,55 @@ impl ClientBuilder {
         self
     }
 
+    /// Override the minimum PoW score of the network, e.g. for private networks with different settings.
+    /// By default the minimum PoW score is read from the node info.
+    pub fn min_pow_score(mut self, min_pow_score: f64) -> Self {
+        self.min_pow_score = Some(min_pow_score);
+        self
+    }
+
+    /// Set the number of workers doing the local PoW. Defaults to the number of CPUs.
+    pub fn pow_worker_count(mut self, worker_count: usize) -> Self {
+        self.pow_worker_count = worker_count.max(1);
+        self
+    }
+
+    /// Set the time after which the PoW of a message is restarted with fresh tips, so messages aren't attached to
+    /// stale parents.
+    pub fn tips_interval(mut self, tips_interval: Duration) -> Self {
+        self.tips_interval = tips_interval;
+        self
+    }
+
+    /// Set the timeout of posting a message when the node does the PoW, which is used when `local_pow` is false and
+    /// no PoW provider is set. Defaults to 30 seconds.
+    pub fn post_message_remote_pow_timeout(mut self, timeout: Duration) -> Self {
+        self.post_message_remote_pow_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum number of derived addresses cached per client, so APIs scanning the same ranges don't derive
+    /// them again. The default is 100000, 0 disables the cache.
+    pub fn address_cache_size(mut self, size: usize) -> Self {
+        self.address_cache_size = size;
+        self
+    }
+
+    /// Set the provider doing the PoW of messages instead of the local miner, e.g. a custom CPU implementation or a
+    /// [`RemotePowProvider`](crate::RemotePowProvider) delegating the PoW to a dedicated server.
+    pub fn pow_provider<P: NonceProvider + 'static>(mut self, provider: P) -> Self {
+        self.pow_providers.set_default(Arc::new(provider));
+        self
+    }
+
+    /// Set the provider doing the PoW of a kind of messages, taking precedence over the one set with
+    /// [`pow_provider`](Self::pow_provider). For example, transactions can be sent to a PoW server while indexation
+    /// messages are mined locally.
+    pub fn pow_provider_for<P: NonceProvider + 'static>(mut self, kind: PayloadKind, provider: P) -> Self {
+        self.pow_providers.set(kind, Arc::new(provider));
+        self
+    }
+
     /// Build the Client instance.
     pub fn build(self) -> Result<Client> {
         if self.nodes.is_empty() {
@@ -137,12 +216,27 @@ impl ClientBuilder {
         let sync = Arc::new(RwLock::new(HashSet::new()));
         let sync_ = sync.clone();
 
+        let network_info = Arc::new(RwLock::new(None));
+        let network_info_ = network_info.clone();
+
+        // Without local PoW, the nodes must do the PoW of the kinds of messages which have no PoW provider, so only
+        // the nodes doing the PoW can be used
+        let remote_pow = !self.local_pow && !self.pow_providers.covers_all_kinds();
+
         let (sync_kill_sender, sync_kill_receiver) = channel(1);
 
         let runtime = std::thread::spawn(move || {
             let mut runtime = Runtime::new().unwrap();
-            runtime.block_on(Client::sync_nodes(&sync_, &nodes));
-            Client::start_sync_process(&runtime, sync_, nodes, node_sync_interval, sync_kill_receiver);
+            runtime.block_on(Client::sync_nodes(&sync_, &nodes, &network_info_, remote_pow));
+            Client::start_sync_process(
+                &runtime,
+                sync_,
+                nodes,
+                node_sync_interval,
+                network_info_,
+                remote_pow,
+                sync_kill_receiver,
+            );
             runtime
         })
         .join()
@@ -159,6 +253,16 @@ impl ClientBuilder {
             mqtt_topic_handlers: Default::default(),
             broker_options: self.broker_options,
             local_pow: self.local_pow,
+            network: self.network,
+            network_info,
+            min_pow_score: self.min_pow_score,
+            pow_worker_count: self.pow_worker_count,
+            tips_interval: self.tips_interval,
+            pow_hash_rate: Default::default(),
+            pow_providers: self.pow_providers,
+            post_message_remote_pow_timeout: self.post_message_remote_pow_timeout,
+            address_cache: Arc::new(RwLock::new(AddressCache::new(self.address_cache_size))),
+            reserved_addresses: Default::default(),
         };
 
         Ok(client)
@@ -3,16 +3,25 @@
 
 //! The Client module to connect through IRI with API usages
 pub use crate::node::Topic;
-use crate::{api::*, builder::ClientBuilder, error::*, node::*, parse_response, types::*};
+use crate::{
+    api::*,
+    builder::{ClientBuilder, Network},
+    error::*,
+    node::*,
+    parse_response,
+    pow::{NonceProvider, PayloadKind, PowHandle, PowProviderRegistry},
+    signing::Signer,
+    types::*,
+};
 
-use bee_message::prelude::{Address, Ed25519Address, Message, MessageId, UTXOInput};
+use bee_message::prelude::{Address, Ed25519Address, Message, MessageId, Payload, UTXOInput};
 use bee_pow::providers::{MinerBuilder, Provider as PowProvider, ProviderBuilder as PowProviderBuilder};
-use bee_signing_ext::Seed;
 
 use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
+use futures::future::try_join_all;
 use paho_mqtt::Client as MqttClient;
 use reqwest::{IntoUrl, Url};
 use serde::{Deserialize, Serialize};
@@ -25,12 +34,15 @@ use tokio::{
 use std::{
     collections::{HashMap, HashSet},
     convert::TryInto,
+    io::Read,
     num::NonZeroU64,
     sync::{Arc, RwLock},
     time::Duration,
 };
 
 const ADDRESS_LENGTH: usize = 32;
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const READ_BUFFER_SIZE: usize = 8 * 1024;
 
 type TopicHandler = Box<dyn Fn(&TopicEvent) + Send + Sync>;
 pub(crate) type TopicHandlerMap = HashMap<Topic, Vec<Arc<TopicHandler>>>;
@@ -90,9 +102,22 @@ impl BrokerOptions {
 }
 
 /// The miner builder.
-#[derive(Default)]
 pub struct ClientMinerBuilder {
     local_pow: bool,
+    worker_count: usize,
+    handle: Option<(PowHandle, u64)>,
+    provider: Option<Arc<dyn NonceProvider>>,
+}
+
+impl Default for ClientMinerBuilder {
+    fn default() -> Self {
+        Self {
+            local_pow: false,
+            worker_count: num_cpus::get(),
+            handle: None,
+            provider: None,
+        }
+    }
 }
 
 impl ClientMinerBuilder {
@@ -101,6 +126,26 @@ impl ClientMinerBuilder {
         self.local_pow = value;
         self
     }
+
+    /// Sets the number of workers doing the PoW
+    pub fn with_worker_count(mut self, worker_count: usize) -> Self {
+        self.worker_count = worker_count.max(1);
+        self
+    }
+
+    /// Sets the handle used to cancel and observe the PoW. The miner is a new attempt of the handle, which abandons
+    /// the previous one.
+    pub fn with_handle(mut self, handle: PowHandle) -> Self {
+        let generation = handle.new_attempt();
+        self.handle = Some((handle, generation));
+        self
+    }
+
+    /// Sets the provider doing the PoW instead of the local miner
+    pub fn with_provider(mut self, provider: Arc<dyn NonceProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
 }
 
 impl PowProviderBuilder for ClientMinerBuilder {
@@ -113,6 +158,9 @@ impl PowProviderBuilder for ClientMinerBuilder {
     fn finish(self) -> ClientMiner {
         ClientMiner {
             local_pow: self.local_pow,
+            worker_count: self.worker_count,
+            handle: self.handle,
+            provider: self.provider,
         }
     }
 }
@@ -120,6 +168,9 @@ impl PowProviderBuilder for ClientMinerBuilder {
 /// The miner used for PoW
 pub struct ClientMiner {
     local_pow: bool,
+    worker_count: usize,
+    handle: Option<(PowHandle, u64)>,
+    provider: Option<Arc<dyn NonceProvider>>,
 }
 
 impl PowProvider for ClientMiner {
@@ -127,12 +178,32 @@ impl PowProvider for ClientMiner {
     type Error = crate::Error;
 
     fn nonce(&self, bytes: &[u8], target_score: f64) -> std::result::Result<u64, Self::Error> {
-        if self.local_pow {
-            MinerBuilder::new()
-                .with_num_workers(num_cpus::get())
+        if let Some(provider) = &self.provider {
+            let (handle, generation) = self.handle.clone().unwrap_or_default();
+            handle.begin_attempt(generation, bytes.len(), target_score);
+            let nonce = provider
+                .nonce(bytes, target_score, &handle.for_attempt(generation))
+                .map_err(|e| {
+                    handle.fail(&e);
+                    e
+                })?;
+            handle.finish_attempt();
+            Ok(nonce)
+        } else if self.local_pow {
+            let (handle, generation) = self.handle.clone().unwrap_or_default();
+            let cancel = handle.begin_attempt(generation, bytes.len(), target_score);
+            let nonce = MinerBuilder::new()
+                .with_num_workers(self.worker_count)
+                .with_cancel(cancel)
                 .finish()
                 .nonce(bytes, target_score)
-                .map_err(|e| crate::Error::Pow(e.to_string()))
+                .map_err(|e| {
+                    let e = crate::Error::Pow(e.to_string());
+                    handle.fail(&e);
+                    e
+                })?;
+            handle.finish_attempt();
+            Ok(nonce)
         } else {
             Ok(0)
         }
@@ -156,6 +227,26 @@ pub struct Client {
     pub(crate) mqtt_topic_handlers: Arc<RwLock<TopicHandlerMap>>,
     pub(crate) broker_options: BrokerOptions,
     pub(crate) local_pow: bool,
+    /// Network of the Iota nodes
+    pub(crate) network: Network,
+    /// Network information of the synced nodes, cached until the next sync
+    pub(crate) network_info: Arc<RwLock<Option<NetworkInfo>>>,
+    /// Minimum PoW score overriding the one of the network
+    pub(crate) min_pow_score: Option<f64>,
+    /// Number of workers doing the PoW
+    pub(crate) pow_worker_count: usize,
+    /// Time after which the PoW is restarted with fresh tips
+    pub(crate) tips_interval: Duration,
+    /// Hash rate estimate of the local PoW, in hashes per second
+    pub(crate) pow_hash_rate: Arc<RwLock<Option<f64>>>,
+    /// PoW providers used instead of the local miner
+    pub(crate) pow_providers: PowProviderRegistry,
+    /// Timeout of posting a message when the node does the PoW
+    pub(crate) post_message_remote_pow_timeout: Duration,
+    /// Addresses derived from seeds
+    pub(crate) address_cache: Arc<RwLock<AddressCache>>,
+    /// Bech32 encoded addresses reserved by `get_unspent_address`
+    pub(crate) reserved_addresses: Arc<RwLock<HashSet<String>>>,
 }
 
 impl std::fmt::Debug for Client {
@@ -167,6 +258,15 @@ impl std::fmt::Debug for Client {
             .field("quorum_threshold", &self.quorum_threshold)
             .field("broker_options", &self.broker_options)
             .field("local_pow", &self.local_pow)
+            .field("network", &self.network)
+            .field("network_info", &self.network_info)
+            .field("min_pow_score", &self.min_pow_score)
+            .field("pow_worker_count", &self.pow_worker_count)
+            .field("tips_interval", &self.tips_interval)
+            .field("pow_providers", &self.pow_providers)
+            .field("post_message_remote_pow_timeout", &self.post_message_remote_pow_timeout)
+            .field("address_cache", &self.address_cache)
+            .field("reserved_addresses", &self.reserved_addresses)
             .finish()
     }
 }
@@ -196,6 +296,8 @@ impl Client {
         sync: Arc<RwLock<HashSet<Url>>>,
         nodes: Vec<Url>,
         node_sync_interval: NonZeroU64,
+        network_info: Arc<RwLock<Option<NetworkInfo>>>,
+        remote_pow: bool,
         mut kill: Receiver<()>,
     ) {
         let node_sync_interval = TokioDuration::from_millis(node_sync_interval.into());
@@ -208,7 +310,7 @@ impl Client {
                                 // delay first since the first `sync_nodes` call is made by the builder
                                 // to ensure the node list is filled before the client is used
                                 delay_for(node_sync_interval).await;
-                                Client::sync_nodes(&sync, &nodes).await;
+                                Client::sync_nodes(&sync, &nodes, &network_info, remote_pow).await;
                         } => {}
                         _ = kill.recv() => {}
                     }
@@ -217,18 +319,31 @@ impl Client {
         });
     }
 
-    pub(crate) async fn sync_nodes(sync: &Arc<RwLock<HashSet<Url>>>, nodes: &[Url]) {
+    pub(crate) async fn sync_nodes(
+        sync: &Arc<RwLock<HashSet<Url>>>,
+        nodes: &[Url],
+        network_info: &Arc<RwLock<Option<NetworkInfo>>>,
+        remote_pow: bool,
+    ) {
         let mut synced_nodes = HashSet::new();
+        let mut synced_network_info = None;
 
         for node_url in nodes {
-            // Put the healty node url into the synced_nodes
-            if Client::get_node_health(node_url.clone()).await.unwrap_or(false) {
-                synced_nodes.insert(node_url.clone());
+            // Put the healty node url into the synced_nodes, skipping the nodes which don't do the PoW if we need
+            // them to
+            if let Ok(info) = Client::get_node_info(node_url.clone()).await {
+                if info.is_healthy && (!remote_pow || info.has_remote_pow()) {
+                    synced_nodes.insert(node_url.clone());
+                    synced_network_info.get_or_insert_with(|| NetworkInfo::from(&info));
+                }
             }
         }
 
-        // Update the sync list
+        // Update the sync list and the network information
         *sync.write().unwrap() = synced_nodes;
+        if let Some(info) = synced_network_info {
+            *network_info.write().unwrap() = Some(info);
+        }
     }
 
     /// Get a node candidate from the synced node pool.
@@ -237,22 +352,60 @@ impl Client {
         Ok(pool.iter().next().ok_or(Error::SyncedNodePoolEmpty)?.clone())
     }
 
+    /// Gets the network information of the nodes we're connecting to, from the cache filled by the node syncing or
+    /// from the node info if the cache is empty.
+    pub async fn get_network_info(&self) -> Result<NetworkInfo> {
+        if let Some(info) = self.network_info.read().unwrap().as_ref() {
+            return Ok(info.clone());
+        }
+        let info = NetworkInfo::from(&self.get_info().await?);
+        *self.network_info.write().unwrap() = Some(info.clone());
+        Ok(info)
+    }
+
     /// Gets the network id of the node we're connecting to.
     pub async fn get_network_id(&self) -> Result<u64> {
-        let info = self.get_info().await?;
-        let mut hasher = VarBlake2b::new(32).unwrap();
-        hasher.update(info.network_id.as_bytes());
-        let mut result: [u8; 32] = [0; 32];
-        hasher.finalize_variable(|res| {
-            result = res.try_into().unwrap();
-        });
-        let network_id = u64::from_le_bytes(result[0..8].try_into().unwrap());
-        Ok(network_id)
+        Ok(self.get_network_info().await?.network_id)
+    }
+
+    /// Gets the minimum PoW score messages must reach, either the one set on the builder or the one of the network.
+    pub async fn get_min_pow_score(&self) -> Result<f64> {
+        match self.min_pow_score {
+            Some(min_pow_score) => Ok(min_pow_score),
+            None => Ok(self.get_network_info().await?.min_pow_score),
+        }
     }
 
-    /// Gets the miner to use based on the PoW setting
-    pub fn get_pow_provider(&self) -> ClientMiner {
-        ClientMinerBuilder::new().with_local_pow(self.local_pow).finish()
+    /// Gets the miner to use for the kind of message based on the PoW setting and the registered providers
+    pub fn get_pow_provider(&self, kind: PayloadKind) -> ClientMiner {
+        self.pow_provider_builder(kind).finish()
+    }
+
+    /// Gets the miner to use for the kind of message based on the PoW setting and the registered providers,
+    /// reporting to the given handle
+    pub(crate) fn get_pow_provider_with_handle(&self, kind: PayloadKind, handle: PowHandle) -> ClientMiner {
+        self.pow_provider_builder(kind).with_handle(handle).finish()
+    }
+
+    fn pow_provider_builder(&self, kind: PayloadKind) -> ClientMinerBuilder {
+        let builder = ClientMinerBuilder::new()
+            .with_local_pow(self.local_pow)
+            .with_worker_count(self.pow_worker_count);
+        match self.pow_providers.get(kind) {
+            Some(provider) => builder.with_provider(provider),
+            None => builder,
+        }
+    }
+
+    /// Whether the PoW of the kind of message is done by the node, because neither local PoW nor a PoW provider is
+    /// used for it
+    pub(crate) fn is_remote_pow(&self, kind: PayloadKind) -> bool {
+        !self.local_pow && self.pow_providers.get(kind).is_none()
+    }
+
+    /// Gets the estimated hash rate of the local PoW in hashes per second, measured on the PoW done so far.
+    pub fn get_pow_hash_rate(&self) -> Option<f64> {
+        *self.pow_hash_rate.read().unwrap()
     }
 
     ///////////////////////////////////////////////////////////////////////
@@ -331,19 +484,23 @@ impl Client {
     }
 
     /// POST /api/v1/messages endpoint
+    /// When the PoW is remote, the nonce is omitted so the node computes it.
     pub async fn post_message(&self, message: &Message) -> Result<MessageId> {
         let mut url = self.get_node()?;
         url.set_path("api/v1/messages");
 
-        let message: MessageJson = message.into();
-
-        let resp = self
+        let remote_pow = self.is_remote_pow(PayloadKind::from(message.payload().as_ref()));
+        let mut message: MessageJson = message.into();
+        let mut request = self
             .client
             .post(url)
-            .header("content-type", "application/json; charset=UTF-8")
-            .json(&message)
-            .send()
-            .await?;
+            .header("content-type", "application/json; charset=UTF-8");
+        if remote_pow {
+            message.remove_nonce();
+            request = request.timeout(self.post_message_remote_pow_timeout);
+        }
+
+        let resp = request.json(&message).send().await?;
 
         parse_response!(resp, 201 => {
             let m = resp.json::<Response<PostMessageId>>().await?.data;
@@ -353,6 +510,11 @@ impl Client {
         })
     }
 
+    /// Build a message, fetching the tips and the network id and doing the PoW, then post it with `post()`.
+    pub fn message(&self) -> ClientMessageBuilder<'_> {
+        ClientMessageBuilder::new(self)
+    }
+
     /// GET /api/v1/messages/{messageId} endpoint
     pub fn get_message(&self) -> GetMessageBuilder<'_> {
         GetMessageBuilder::new(self)
@@ -443,14 +605,11 @@ impl Client {
         let message = self.get_message().data(message_id).await?;
 
         // Change the fields of parent1 and parent2.
-        let tips = self.get_tips().await?;
-        let reattach_message = Message::builder()
-            .with_network_id(self.get_network_id().await?)
-            .with_parent1(tips.0)
-            .with_parent2(tips.1)
+        let reattach_message = self
+            .message()
             .with_payload(message.payload().to_owned().unwrap())
             .finish()
-            .map_err(|_| Error::TransactionError)?;
+            .await?;
 
         // Post the modified
         let message_id = self.post_message(&reattach_message).await?;
@@ -462,12 +621,7 @@ impl Client {
     pub async fn promote(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
         // Create a new message (zero value message) for which one tip would be the actual message
         let tips = self.get_tips().await?;
-        let promote_message = Message::builder()
-            .with_network_id(self.get_network_id().await?)
-            .with_parent1(tips.0)
-            .with_parent2(*message_id)
-            .finish()
-            .map_err(|_| Error::TransactionError)?;
+        let promote_message = self.message().with_parents(tips.0, *message_id).finish().await?;
 
         let message_id = self.post_message(&promote_message).await?;
         Ok((message_id, promote_message))
@@ -478,23 +632,95 @@ impl Client {
     //////////////////////////////////////////////////////////////////////
 
     /// A generic send function for easily sending value transaction messages.
-    pub fn send<'a>(&'a self, seed: &'a Seed) -> SendBuilder<'a> {
-        SendBuilder::new(self, seed)
+    pub fn send<'a>(&'a self, signer: &'a dyn Signer) -> SendBuilder<'a> {
+        SendBuilder::new(self, signer)
+    }
+
+    /// Prepare a transaction spending from the given addresses of a seed without the seed, to be signed with
+    /// [`sign_transaction`](crate::api::sign_transaction) by its holder. The addresses are exported with
+    /// [`derive_address_paths`](crate::api::derive_address_paths).
+    pub fn prepare_transaction<'a>(&'a self, address_paths: &'a [AddressPath]) -> SendBuilder<'a> {
+        SendBuilder::with_address_paths(self, address_paths)
+    }
+
+    /// Consolidate the unspent outputs of the seed into a few outputs on one address, in transactions within the
+    /// input limit of the protocol.
+    pub fn consolidate<'a>(&'a self, signer: &'a dyn Signer) -> ConsolidateBuilder<'a> {
+        ConsolidateBuilder::new(self, signer)
+    }
+
+    /// Send payouts to many recipients, split into transactions within the output limit of the protocol which spend
+    /// the remainder of the previous one.
+    pub fn batch_send<'a>(&'a self, signer: &'a dyn Signer) -> BatchSendBuilder<'a> {
+        BatchSendBuilder::new(self, signer)
+    }
+
+    /// Attach a transaction signed with [`sign_transaction`](crate::api::sign_transaction) to the Tangle, doing the
+    /// PoW, and post it. This is the last step of `send(seed).prepare()`, used to sign transactions offline.
+    pub async fn submit_transaction(&self, signed: SignedTransaction) -> Result<MessageId> {
+        let payload = get_payload_from_json(signed.payload, None)?;
+        if !matches!(payload, Payload::Transaction(_)) {
+            return Err(Error::InvalidParameter(String::from("transaction payload")));
+        }
+        self.message().with_payload(payload).post().await
+    }
+
+    /// Notarize a document by posting the BLAKE2b-256 hash of its content in an indexation payload. The returned
+    /// receipt can be used with `verify_notarization()` later.
+    pub fn notarize<R: Read + Send + 'static>(&self, reader: R) -> NotarizeBuilder<'_, R> {
+        NotarizeBuilder::new(self, reader)
+    }
+
+    /// Verify that a document matches a notarization receipt and that the notarization message is on the Tangle.
+    pub fn verify_notarization<'a, R: Read + Send + 'static>(
+        &'a self,
+        receipt: &'a NotarizationReceipt,
+        reader: R,
+    ) -> VerifyNotarizationBuilder<'a, R> {
+        VerifyNotarizationBuilder::new(self, receipt, reader)
+    }
+
+    /// Release an address reserved by `get_unspent_address`, e.g. when it wasn't handed out after all.
+    pub fn release_address(&self, address: &Address) {
+        self.reserved_addresses.write().unwrap().remove(&address.to_bech32());
     }
 
     /// Return a valid unuspent address.
-    pub fn get_unspent_address<'a>(&'a self, seed: &'a Seed) -> GetUnspentAddressBuilder<'a> {
-        GetUnspentAddressBuilder::new(self, seed)
+    pub fn get_unspent_address<'a>(&'a self, signer: &'a dyn Signer) -> GetUnspentAddressBuilder<'a> {
+        GetUnspentAddressBuilder::new(self, signer)
     }
 
     /// Return a list of addresses from the seed regardless of their validity.
-    pub fn find_addresses<'a>(&'a self, seed: &'a Seed) -> GetAddressesBuilder<'a> {
-        GetAddressesBuilder::new(self, seed)
+    pub fn find_addresses<'a>(&'a self, signer: &'a dyn Signer) -> GetAddressesBuilder<'a> {
+        GetAddressesBuilder::new(self, signer)
+    }
+
+    /// Return every used address of the seed with its index and balance, searching the chains until the gap limit
+    /// of consecutive addresses which never received any output.
+    pub fn discover_account<'a>(&'a self, signer: &'a dyn Signer) -> DiscoverAccountBuilder<'a> {
+        DiscoverAccountBuilder::new(self, signer)
+    }
+
+    /// Return the transactions which touched the used addresses of the seed, classified as incoming, outgoing or
+    /// internal, with the milestone which confirmed them.
+    pub fn get_transaction_history<'a>(&'a self, signer: &'a dyn Signer) -> GetTransactionHistoryBuilder<'a> {
+        GetTransactionHistoryBuilder::new(self, signer)
+    }
+
+    /// Forget the addresses derived from seeds, e.g. before dropping a seed that shouldn't be linked to them anymore.
+    pub fn clear_address_cache(&self) {
+        self.address_cache.write().unwrap().clear();
     }
 
-    /// Find all messages by provided message IDs. This method will try to query multiple nodes
-    /// if the request amount exceed individual node limit.
-    pub async fn find_messages(&self, indexation_keys: &[String], message_ids: &[MessageId]) -> Result<Vec<Message>> {
+    /// Find all messages by provided message IDs and indexation keys. This method will try to query multiple nodes
+    /// if the request amount exceed individual node limit. Indexation keys are raw bytes; messages found through them
+    /// are only returned if their indexation payload matches one of the keys byte for byte. Indexation payloads store
+    /// their index as a UTF-8 string, so a key which isn't valid UTF-8 never matches a message.
+    pub async fn find_messages<I: AsRef<[u8]>>(
+        &self,
+        indexation_keys: &[I],
+        message_ids: &[MessageId],
+    ) -> Result<Vec<Message>> {
         let mut messages = Vec::new();
 
         // Use a `HashSet` to prevent duplicate message_ids.
@@ -505,30 +731,36 @@ impl Client {
             message_ids_to_query.insert(message_id.to_owned());
         }
 
-        // Use `get_message().index()` API to get the message ID first,
-        // then collect the `MessageId` in the HashSet.
-        for index in indexation_keys {
-            let message_ids = self.get_message().index(&index).await?;
-            for message_id in message_ids.iter() {
-                message_ids_to_query.insert(message_id.to_owned());
-            }
-        }
-
         // Use `get_message().data()` API to get the `Message`.
-        for message_id in message_ids_to_query {
-            let message = self.get_message().data(&message_id).await.unwrap();
+        for message_id in message_ids_to_query.iter() {
+            let message = self.get_message().data(message_id).await?;
             messages.push(message);
         }
 
+        // Use `get_message().index()` API to get the message ID first, then keep only the messages whose
+        // indexation key is exactly the requested one.
+        for index in indexation_keys {
+            let index = index.as_ref();
+            let index_message_ids = self.get_message().index(index).await?;
+            for message_id in index_message_ids.iter() {
+                if !message_ids_to_query.insert(message_id.to_owned()) {
+                    continue;
+                }
+                let message = self.get_message().data(message_id).await?;
+                if has_indexation_key(&message, index) {
+                    messages.push(message);
+                }
+            }
+        }
+
         Ok(messages)
     }
 
     /// Return the balance for a provided seed and its wallet chain BIP32 path. BIP32 derivation path
     /// of the address should be in form of `m/0'/0'/k'`. So the wallet chain is expected to be `m/0'/0'`.
-    /// Addresses with balance must be consecutive, so this method will return once it encounters a zero
-    /// balance address.
-    pub fn get_balance<'a>(&'a self, seed: &'a Seed) -> GetBalanceBuilder<'a> {
-        GetBalanceBuilder::new(self, seed)
+    /// The chain is searched until the gap limit of consecutive addresses which never received any output.
+    pub fn get_balance<'a>(&'a self, signer: &'a dyn Signer) -> GetBalanceBuilder<'a> {
+        GetBalanceBuilder::new(self, signer)
     }
 
     /// Return the balance in iota for the given addresses; No seed or security level needed to do this
@@ -545,6 +777,154 @@ impl Client {
         Ok(address_balance_pairs)
     }
 
+    /// Return the transactions which touched the given addresses, classified as incoming, outgoing or internal
+    /// relative to the whole set, with the milestone which confirmed them. No seed is needed.
+    pub async fn get_address_history(&self, addresses: &[Address]) -> Result<TransactionHistory> {
+        let outputs = try_join_all(
+            addresses
+                .iter()
+                .map(|address| self.get_address().include_spent(true).outputs(address)),
+        )
+        .await?;
+        let output_ids: Vec<UTXOInput> = outputs.into_iter().flat_map(|outputs| outputs.into_vec()).collect();
+        collect_history(self, addresses, &output_ids).await
+    }
+
+    /// Publish data that doesn't fit in a single indexation payload. The data is split in chunks of
+    /// [`DATA_CHUNK_SIZE`] bytes, each posted as an indexation message with the given index and linked to the previous
+    /// chunk through its second parent. A manifest message carrying the chunk order and the data hash is posted last,
+    /// and its `MessageId` is returned to be used with `fetch_data()`.
+    pub async fn publish_data<I: AsRef<str>, D: AsRef<[u8]>>(&self, index: I, data: D) -> Result<MessageId> {
+        let index = index.as_ref();
+        let data = data.as_ref();
+        let mut chunks = Vec::new();
+        let mut previous_chunk: Option<MessageId> = None;
+
+        for chunk in data.chunks(DATA_CHUNK_SIZE) {
+            let message_id = self
+                .post_linked_indexation(index, chunk, previous_chunk.as_ref())
+                .await?;
+            chunks.push(message_id.to_string());
+            previous_chunk = Some(message_id);
+        }
+
+        let manifest = DataManifest {
+            version: DATA_MANIFEST_VERSION,
+            size: data.len(),
+            hash: hex::encode(hash_data(data)),
+            chunks,
+        };
+        let manifest = serde_json::to_vec(&manifest)?;
+        if manifest.len() > DATA_CHUNK_SIZE {
+            return Err(Error::InvalidParameter(format!(
+                "data of {} bytes needs too many chunks to fit in a manifest",
+                data.len()
+            )));
+        }
+
+        self.post_linked_indexation(index, &manifest, previous_chunk.as_ref())
+            .await
+    }
+
+    /// Fetch data published with `publish_data()` from its manifest message. The chunks are reassembled in order and
+    /// the size and hash recorded in the manifest are checked against the result.
+    pub async fn fetch_data(&self, manifest_id: &MessageId) -> Result<Vec<u8>> {
+        let manifest_message = self.get_message().data(manifest_id).await?;
+        let (index, manifest) = match manifest_message.payload() {
+            Some(Payload::Indexation(indexation)) => (indexation.index().to_string(), indexation.data().to_vec()),
+            _ => {
+                return Err(Error::DataIntegrity(
+                    "manifest message has no indexation payload".to_string(),
+                ))
+            }
+        };
+        let manifest: DataManifest = serde_json::from_slice(&manifest)?;
+        if manifest.version != DATA_MANIFEST_VERSION {
+            return Err(Error::DataIntegrity(format!(
+                "unsupported manifest version {}",
+                manifest.version
+            )));
+        }
+
+        // Don't trust the size for the allocation, each chunk holds at most DATA_CHUNK_SIZE bytes
+        if manifest.size > manifest.chunks.len().saturating_mul(DATA_CHUNK_SIZE) {
+            return Err(Error::DataIntegrity(format!(
+                "size of {} bytes doesn't fit in {} chunks",
+                manifest.size,
+                manifest.chunks.len()
+            )));
+        }
+        let mut data = Vec::with_capacity(manifest.size);
+        for chunk_id in manifest.chunks.iter() {
+            let mut message_id = [0u8; 32];
+            hex::decode_to_slice(chunk_id, &mut message_id)?;
+            let chunk = self.get_message().data(&MessageId::from(message_id)).await?;
+            match chunk.payload() {
+                Some(Payload::Indexation(indexation)) if indexation.index() == index.as_str() => {
+                    data.extend_from_slice(indexation.data())
+                }
+                _ => {
+                    return Err(Error::DataIntegrity(format!(
+                        "chunk {} isn't an indexation payload with index {}",
+                        chunk_id, index
+                    )))
+                }
+            }
+        }
+
+        if data.len() != manifest.size {
+            return Err(Error::DataIntegrity(format!(
+                "expected {} bytes but reassembled {}",
+                manifest.size,
+                data.len()
+            )));
+        }
+        if hex::encode(hash_data(&data)) != manifest.hash {
+            return Err(Error::DataIntegrity("hash mismatch".to_string()));
+        }
+
+        Ok(data)
+    }
+
+    /// Post an indexation message whose second parent is the given message, or a tip if there's none.
+    async fn post_linked_indexation(
+        &self,
+        index: &str,
+        data: &[u8],
+        previous: Option<&MessageId>,
+    ) -> Result<MessageId> {
+        let mut message = self.message().with_index(index).with_data(data.to_vec());
+        if let Some(previous) = previous {
+            let tips = self.get_tips().await?;
+            message = message.with_parents(tips.0, *previous);
+        }
+        message.post().await
+    }
+
+    /// Wait until the message is included in the ledger, promoting or reattaching it when needed. Get the id of the
+    /// included message, which is a reattachment if the message was reattached.
+    pub async fn wait_for_confirmation(&self, message_id: &MessageId, timeout: Duration) -> Result<MessageId> {
+        let start = std::time::Instant::now();
+        let mut message_id = *message_id;
+        loop {
+            let metadata = self.get_message().metadata(&message_id).await?;
+            match metadata.ledger_inclusion_state.as_deref() {
+                Some("included") => return Ok(message_id),
+                Some(state) => return Err(Error::MessageNotIncluded(message_id.to_string(), state.to_string())),
+                None => {}
+            }
+            if start.elapsed() >= timeout {
+                return Err(Error::ConfirmationTimeout(message_id.to_string()));
+            }
+            if metadata.should_reattach.unwrap_or(false) {
+                message_id = self.reattach(&message_id).await?.0;
+            } else if metadata.should_promote.unwrap_or(false) {
+                self.promote(&message_id).await?;
+            }
+            delay_for(CONFIRMATION_POLL_INTERVAL).await;
+        }
+    }
+
     /// Retries (promotes or reattaches) a message for provided message id. Message should only be
     /// retried only if they are valid and haven't been confirmed for a while.
     pub async fn retry(&self, message_id: &MessageId) -> Result<(MessageId, Message)> {
@@ -559,3 +939,45 @@ impl Client {
         }
     }
 }
+
+/// Compute the BLAKE2b-256 hash of the data.
+pub(crate) fn hash_data(data: &[u8]) -> [u8; 32] {
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(data);
+    finalize_hash(hasher)
+}
+
+/// Compute the BLAKE2b-256 hash of the content of the reader, streamed so it's never loaded in memory at once.
+pub(crate) fn hash_reader<R: Read>(reader: &mut R) -> std::io::Result<[u8; 32]> {
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    let mut buffer = [0u8; READ_BUFFER_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(finalize_hash(hasher))
+}
+
+fn finalize_hash(hasher: VarBlake2b) -> [u8; 32] {
+    let mut result: [u8; 32] = [0; 32];
+    hasher.finalize_variable(|res| {
+        result = res.try_into().unwrap();
+    });
+    result
+}
+
+/// Check whether the message carries an indexation payload, directly or inside a transaction essence, with exactly the
+/// given index bytes.
+fn has_indexation_key(message: &Message, index: &[u8]) -> bool {
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => indexation.index().as_bytes() == index,
+        Some(Payload::Transaction(transaction)) => match transaction.essence().payload() {
+            Some(Payload::Indexation(indexation)) => indexation.index().as_bytes() == index,
+            _ => false,
+        },
+        _ => false,
+    }
+}
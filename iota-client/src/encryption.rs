@@ -0,0 +1,200 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encryption of indexation payload data for a list of recipients.
+//!
+//! The data is encrypted with XChaCha20-Poly1305 under a random content key. The content key is then wrapped for every
+//! recipient with a key agreed through X25519 between an ephemeral key pair and the recipient's public key, and derived
+//! from the shared secret with keyed BLAKE2b-256 under a context label. The result is a versioned envelope which is
+//! stored as the data of the indexation payload:
+//!
+//! | Field | Size |
+//! | - | - |
+//! | version | 1 |
+//! | ephemeral public key | 32 |
+//! | recipient count | 1 |
+//! | per recipient: key id, wrap nonce, wrapped content key | 8 + 24 + 48 |
+//! | content nonce | 24 |
+//! | ciphertext | remaining bytes |
+//!
+//! The header is authenticated along with the keys and the data: every wrapped key is bound to the version, the
+//! ephemeral public key and the recipient count, and the ciphertext to everything which precedes its nonce, so no
+//! recipient can be added, removed or swapped without the decryption failing.
+
+use crate::{client::hash_data, Error, Result};
+
+use bee_message::prelude::{Indexation, Message, Payload};
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload as AeadPayload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+
+pub use x25519_dalek::{PublicKey, StaticSecret as SecretKey};
+
+/// Current version of the envelope format.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+const KEY_LENGTH: usize = 32;
+const KEY_ID_LENGTH: usize = 8;
+const NONCE_LENGTH: usize = 24;
+const TAG_LENGTH: usize = 16;
+const WRAPPED_KEY_LENGTH: usize = KEY_LENGTH + TAG_LENGTH;
+const RECIPIENT_LENGTH: usize = KEY_ID_LENGTH + NONCE_LENGTH + WRAPPED_KEY_LENGTH;
+const HEADER_LENGTH: usize = 1 + KEY_LENGTH + 1;
+/// Context label of the derivation of the wrapping keys, so they are never reused for another purpose.
+const WRAPPING_KEY_CONTEXT: &[u8] = b"iota.rs envelope wrapping key";
+
+/// Generate a new X25519 key pair for a recipient of encrypted payloads.
+pub fn generate_key_pair() -> (SecretKey, PublicKey) {
+    let secret = SecretKey::new(&mut OsRng);
+    let public = PublicKey::from(&secret);
+    (secret, public)
+}
+
+/// Encrypt the data for the given recipients and return the envelope.
+pub fn encrypt(data: &[u8], recipients: &[PublicKey]) -> Result<Vec<u8>> {
+    if recipients.is_empty() || recipients.len() > u8::MAX as usize {
+        return Err(Error::InvalidParameter(format!(
+            "recipient count must be between 1 and {}",
+            u8::MAX
+        )));
+    }
+
+    let mut content_key = [0u8; KEY_LENGTH];
+    OsRng.fill_bytes(&mut content_key);
+    let ephemeral_secret = SecretKey::new(&mut OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral_secret);
+
+    let mut envelope = vec![ENVELOPE_VERSION];
+    envelope.extend_from_slice(ephemeral_public.as_bytes());
+    envelope.push(recipients.len() as u8);
+
+    let header = envelope.clone();
+    for recipient in recipients {
+        let shared_secret = ephemeral_secret.diffie_hellman(recipient);
+        let wrapping_key = derive_wrapping_key(shared_secret.as_bytes(), &ephemeral_public, recipient);
+        let nonce = random_nonce();
+        let wrapped_key = seal(&wrapping_key, &nonce, &content_key, &header)?;
+
+        envelope.extend_from_slice(&key_id(recipient));
+        envelope.extend_from_slice(&nonce);
+        envelope.extend_from_slice(&wrapped_key);
+    }
+
+    let nonce = random_nonce();
+    let ciphertext = seal(&content_key, &nonce, data, &envelope)?;
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+
+    Ok(envelope)
+}
+
+/// Decrypt an envelope with the secret key of one of its recipients.
+pub fn decrypt(envelope: &[u8], secret: &SecretKey) -> Result<Vec<u8>> {
+    if envelope.len() < HEADER_LENGTH {
+        return Err(Error::Encryption("envelope is too short".to_string()));
+    }
+    if envelope[0] != ENVELOPE_VERSION {
+        return Err(Error::Encryption(format!(
+            "unsupported envelope version {}",
+            envelope[0]
+        )));
+    }
+
+    let mut ephemeral_public = [0u8; KEY_LENGTH];
+    ephemeral_public.copy_from_slice(&envelope[1..1 + KEY_LENGTH]);
+    let ephemeral_public = PublicKey::from(ephemeral_public);
+    let recipient_count = envelope[1 + KEY_LENGTH] as usize;
+
+    let content_offset = HEADER_LENGTH + recipient_count * RECIPIENT_LENGTH;
+    if envelope.len() < content_offset + NONCE_LENGTH + TAG_LENGTH {
+        return Err(Error::Encryption("envelope is too short".to_string()));
+    }
+
+    let public = PublicKey::from(secret);
+    let id = key_id(&public);
+    let shared_secret = secret.diffie_hellman(&ephemeral_public);
+    let wrapping_key = derive_wrapping_key(shared_secret.as_bytes(), &ephemeral_public, &public);
+
+    let header = &envelope[..HEADER_LENGTH];
+    let content_key = envelope[HEADER_LENGTH..content_offset]
+        .chunks(RECIPIENT_LENGTH)
+        .filter(|recipient| recipient[..KEY_ID_LENGTH] == id)
+        .find_map(|recipient| {
+            let nonce = &recipient[KEY_ID_LENGTH..KEY_ID_LENGTH + NONCE_LENGTH];
+            open(&wrapping_key, nonce, &recipient[KEY_ID_LENGTH + NONCE_LENGTH..], header).ok()
+        })
+        .ok_or_else(|| Error::Encryption("the key isn't a recipient of the envelope".to_string()))?;
+
+    let nonce = &envelope[content_offset..content_offset + NONCE_LENGTH];
+    open(
+        &content_key,
+        nonce,
+        &envelope[content_offset + NONCE_LENGTH..],
+        &envelope[..content_offset],
+    )
+}
+
+/// Create an indexation payload whose data is encrypted for the given recipients.
+pub fn encrypt_indexation<I: Into<String>>(index: I, data: &[u8], recipients: &[PublicKey]) -> Result<Indexation> {
+    Ok(Indexation::new(index.into(), &encrypt(data, recipients)?)?)
+}
+
+/// Decrypt the data of an indexation payload created with `encrypt_indexation()`.
+pub fn decrypt_indexation(indexation: &Indexation, secret: &SecretKey) -> Result<Vec<u8>> {
+    decrypt(indexation.data(), secret)
+}
+
+/// Decrypt the indexation payload of a message, e.g. one fetched with `GetMessageBuilder::data`. The indexation payload
+/// may be the message payload itself or the one embedded in a transaction essence.
+pub fn decrypt_message(message: &Message, secret: &SecretKey) -> Result<Vec<u8>> {
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => decrypt_indexation(indexation, secret),
+        Some(Payload::Transaction(transaction)) => match transaction.essence().payload() {
+            Some(Payload::Indexation(indexation)) => decrypt_indexation(indexation, secret),
+            _ => Err(Error::Encryption("message has no indexation payload".to_string())),
+        },
+        _ => Err(Error::Encryption("message has no indexation payload".to_string())),
+    }
+}
+
+// BLAKE2b keyed with the shared secret is a PRF, so it's used as the KDF, with the public keys binding the wrapping key
+// to this exchange.
+fn derive_wrapping_key(shared_secret: &[u8], ephemeral_public: &PublicKey, recipient: &PublicKey) -> [u8; KEY_LENGTH] {
+    let mut kdf = VarBlake2b::new_keyed(shared_secret, KEY_LENGTH);
+    kdf.update(WRAPPING_KEY_CONTEXT);
+    kdf.update(ephemeral_public.as_bytes());
+    kdf.update(recipient.as_bytes());
+    let mut key = [0u8; KEY_LENGTH];
+    kdf.finalize_variable(|result| key.copy_from_slice(result));
+    key
+}
+
+fn key_id(public: &PublicKey) -> [u8; KEY_ID_LENGTH] {
+    let mut id = [0u8; KEY_ID_LENGTH];
+    id.copy_from_slice(&hash_data(public.as_bytes())[..KEY_ID_LENGTH]);
+    id
+}
+
+fn random_nonce() -> [u8; NONCE_LENGTH] {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn seal(key: &[u8], nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(XNonce::from_slice(nonce), AeadPayload { msg: plaintext, aad })
+        .map_err(|_| Error::Encryption("failed to encrypt".to_string()))
+}
+
+fn open(key: &[u8], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(nonce), AeadPayload { msg: ciphertext, aad })
+        .map_err(|_| Error::Encryption("failed to decrypt".to_string()))
+}
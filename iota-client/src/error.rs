@@ -3,6 +3,8 @@
 
 //! Error handling in iota-client crate.
 
+use crate::builder::Network;
+
 /// Type alias of `Result` in iota-client
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -15,12 +17,45 @@ pub enum Error {
     /// The wallet account doesn't have enough balance
     #[error("The wallet account doesn't have enough balance. It only has {0}")]
     NotEnoughBalance(u64),
+    /// Bech32 decoding error
+    #[error("{0}")]
+    Bech32(#[from] bech32::Error),
+    /// The bech32 HRP isn't the one of any network
+    #[error("Unknown bech32 HRP {0}")]
+    UnknownBech32Hrp(String),
+    /// The bech32 HRP isn't the one of the network
+    #[error("Bech32 HRP {0} doesn't belong to the {1:?} network")]
+    Bech32HrpMismatch(String, Network),
+    /// The address isn't a valid Ed25519 address
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
     /// Missing required parameters
     #[error("Must provide required parameter: {0}")]
     MissingParameter(String),
     /// Invalid parameters
     #[error("Parameter is invalid:{0}")]
     InvalidParameter(String),
+    /// The amount can't be covered without exceeding the maximum number of inputs of a transaction
+    #[error("The amount can't be covered with at most {0} inputs")]
+    TooManyInputs(usize),
+    /// The transaction needs more outputs than the maximum number of outputs of a transaction
+    #[error("The transaction needs {0} outputs, more than the maximum of {1}")]
+    TooManyOutputs(usize, usize),
+    /// The address of a given input doesn't belong to the seed
+    #[error("Input {0} doesn't belong to the seed in the searched address range")]
+    InputAddressNotFound(String),
+    /// Every address of the searched range already received outputs
+    #[error("No unused address in the searched address range {0}")]
+    UnusedAddressNotFound(String),
+    /// The message was referenced by a milestone without being included in the ledger, e.g. it's conflicting
+    #[error("Message {0} isn't included in the ledger: {1}")]
+    MessageNotIncluded(String, String),
+    /// The message wasn't confirmed in time
+    #[error("Message {0} wasn't confirmed in time")]
+    ConfirmationTimeout(String),
+    /// The outcome of a transaction whose posting failed is still unknown
+    #[error("Transaction {0} is neither in the ledger nor conflicting, so it may still be included")]
+    TransactionUnresolved(String),
     /// Found Spent Address that still has balance
     #[error("Found Spent Address that still has balance.")]
     SpentAddress,
@@ -63,6 +98,27 @@ pub enum Error {
     /// PoW error
     #[error("{0}")]
     Pow(String),
+    /// The PoW was cancelled through its handle
+    #[error("PoW was cancelled")]
+    PowCancelled,
+    /// The PoW didn't finish in time
+    #[error("PoW timed out")]
+    PowTimeout,
+    /// Encryption or decryption of an indexation envelope failed
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+    /// A signer failed to derive a key or to sign
+    #[error("Signer error: {0}")]
+    Signer(String),
+    /// The mnemonic is invalid
+    #[error("Invalid mnemonic: {0}")]
+    Mnemonic(String),
+    /// A keystore can't be created or unlocked
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+    /// Data fetched from the Tangle doesn't match its manifest
+    #[error("Data integrity check failed: {0}")]
+    DataIntegrity(String),
 }
 
 // can't use #[from] on bee_message::Error so manually converting it
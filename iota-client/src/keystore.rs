@@ -0,0 +1,329 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! File-based keystore holding a seed encrypted with a password.
+//!
+//! The seed is encrypted with XChaCha20-Poly1305 under a key derived from the password with Argon2id, and stored as a
+//! versioned JSON file. Unlocking a keystore returns a [`ZeroizingSeed`], so the seed doesn't need to be kept in an
+//! environment variable anymore. Backups use the same format, encrypted with their own password.
+
+use crate::{
+    signing::{Signer, SignerFuture},
+    Error, Result,
+};
+
+use bee_signing_ext::binary::BIP32Path;
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey};
+use futures::future::try_join_all;
+use hmac::{Hmac, Mac, NewMac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Version of the keystore file format
+pub const KEYSTORE_VERSION: u8 = 1;
+
+const KDF_ALGORITHM: &str = "argon2id";
+const CIPHER_ALGORITHM: &str = "xchacha20poly1305";
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+const HARDEND: u32 = 1 << 31;
+
+/// Parameters of the Argon2id key derivation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    /// Memory cost in KiB
+    #[serde(rename = "memCost")]
+    mem_cost: u32,
+    #[serde(rename = "timeCost")]
+    time_cost: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+impl KdfParams {
+    fn new() -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: String::from(KDF_ALGORITHM),
+            mem_cost: 64 * 1024,
+            time_cost: 3,
+            parallelism: 1,
+            salt: hex::encode(salt),
+        }
+    }
+
+    fn derive_key(&self, password: &str) -> Result<Zeroizing<[u8; KEY_LENGTH]>> {
+        if self.algorithm != KDF_ALGORITHM {
+            return Err(Error::Keystore(format!("unsupported KDF {}", self.algorithm)));
+        }
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.mem_cost,
+            time_cost: self.time_cost,
+            lanes: self.parallelism,
+            thread_mode: argon2::ThreadMode::Sequential,
+            secret: &[],
+            ad: &[],
+            hash_length: KEY_LENGTH as u32,
+        };
+        let hash = Zeroizing::new(
+            argon2::hash_raw(password.as_bytes(), &hex::decode(&self.salt)?, &config)
+                .map_err(|e| Error::Keystore(e.to_string()))?,
+        );
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        key.copy_from_slice(&hash);
+        Ok(key)
+    }
+}
+
+/// Content of a keystore file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u8,
+    kdf: KdfParams,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl KeystoreFile {
+    fn seal(seed: &[u8], password: &str) -> Result<Self> {
+        let kdf = KdfParams::new();
+        let key = kdf.derive_key(password)?;
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .encrypt(XNonce::from_slice(&nonce), seed)
+            .map_err(|_| Error::Keystore(String::from("encryption failed")))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf,
+            cipher: String::from(CIPHER_ALGORITHM),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    fn open(&self, password: &str) -> Result<Vec<u8>> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::Keystore(format!("unsupported version {}", self.version)));
+        }
+        if self.cipher != CIPHER_ALGORITHM {
+            return Err(Error::Keystore(format!("unsupported cipher {}", self.cipher)));
+        }
+        let key = self.kdf.derive_key(password)?;
+        let nonce = hex::decode(&self.nonce)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(Error::Keystore(String::from("invalid nonce")));
+        }
+        XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .decrypt(XNonce::from_slice(&nonce), hex::decode(&self.ciphertext)?.as_ref())
+            .map_err(|_| Error::Keystore(String::from("wrong password or corrupted keystore")))
+    }
+}
+
+/// Encrypt the Ed25519 seed bytes with the password and write them to a new keystore file. An existing file isn't
+/// overwritten.
+pub fn create<P: AsRef<Path>>(path: P, seed: &[u8], password: &str) -> Result<()> {
+    let seed = ZeroizingSeed::from_bytes(seed)?;
+    write(path.as_ref(), &KeystoreFile::seal(&seed.bytes[..], password)?, false)
+}
+
+/// Read the keystore file and unlock its seed with the password.
+pub fn open<P: AsRef<Path>>(path: P, password: &str) -> Result<ZeroizingSeed> {
+    let seed = Zeroizing::new(read(path.as_ref())?.open(password)?);
+    ZeroizingSeed::from_bytes(&seed)
+}
+
+/// Encrypt the seed of the keystore file with a new password. The file is replaced only once the new one is written.
+pub fn change_password<P: AsRef<Path>>(path: P, password: &str, new_password: &str) -> Result<()> {
+    let seed = open(path.as_ref(), password)?;
+    write(path.as_ref(), &KeystoreFile::seal(&seed.bytes[..], new_password)?, true)
+}
+
+/// Export a backup of the keystore file, encrypted with the backup password.
+pub fn export<P: AsRef<Path>, B: AsRef<Path>>(path: P, password: &str, backup: B, backup_password: &str) -> Result<()> {
+    let seed = open(path, password)?;
+    write(
+        backup.as_ref(),
+        &KeystoreFile::seal(&seed.bytes[..], backup_password)?,
+        false,
+    )
+}
+
+/// Import a backup exported with [`export`] into a new keystore file, encrypted with the password.
+pub fn import<B: AsRef<Path>, P: AsRef<Path>>(backup: B, backup_password: &str, path: P, password: &str) -> Result<()> {
+    let seed = open(backup, backup_password)?;
+    write(path.as_ref(), &KeystoreFile::seal(&seed.bytes[..], password)?, false)
+}
+
+fn read(path: &Path) -> Result<KeystoreFile> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+// Write the file next to its destination first, so a failure never leaves a truncated keystore behind. Unless it's
+// overwritten, the destination is created with a hard link, which fails if another writer created it meanwhile.
+fn write(path: &Path, file: &KeystoreFile, overwrite: bool) -> Result<()> {
+    if !overwrite && path.exists() {
+        return Err(already_exists(path));
+    }
+    // Each writer has its own temporary file, so concurrent writers don't write into each other's
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{:016x}.tmp", OsRng.next_u64()));
+    let tmp = PathBuf::from(tmp);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options.open(&tmp).map_err(Error::from).and_then(|mut writer| {
+        writer.write_all(serde_json::to_string_pretty(file)?.as_bytes())?;
+        writer.sync_all()?;
+        if overwrite {
+            return Ok(fs::rename(&tmp, path)?);
+        }
+        match fs::hard_link(&tmp, path) {
+            Ok(()) => {
+                // The keystore is written, a leftover temporary file is only a stray copy
+                let _ = fs::remove_file(&tmp);
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(already_exists(path)),
+            Err(e) => Err(e.into()),
+        }
+    });
+    if result.is_err() {
+        // The file may have been created, even partially written
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn already_exists(path: &Path) -> Error {
+    Error::Keystore(format!("{} already exists", path.display()))
+}
+
+/// An Ed25519 seed whose bytes are cleared from memory when it's dropped.
+///
+/// The keys are derived with SLIP-0010 from the bytes themselves, and the intermediate keys are cleared as well, so no
+/// copy of the seed or of its keys outlives a derivation.
+pub struct ZeroizingSeed {
+    bytes: Zeroizing<Vec<u8>>,
+}
+
+impl ZeroizingSeed {
+    /// Create the seed from its bytes, e.g. the ones of a hex seed which was kept in an environment variable before
+    /// moving it to a keystore. A seed has between 16 and 64 bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 16 || bytes.len() > 64 {
+            return Err(Error::Keystore(String::from("invalid seed")));
+        }
+        Ok(Self {
+            bytes: Zeroizing::new(bytes.to_vec()),
+        })
+    }
+}
+
+fn secret_key(seed: &[u8], path: &BIP32Path) -> Result<SecretKey> {
+    let mut key = hmac_sha512(b"ed25519 seed", &[seed]);
+    for index in hardened_indexes(path)? {
+        key = hmac_sha512(&key[32..], &[&[0], &key[..32], &index.to_be_bytes()]);
+    }
+    SecretKey::from_bytes(&key[..32]).map_err(|e| Error::Signer(e.to_string()))
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Hmac::<Sha512>::new_varkey(key).expect("HMAC takes keys of any length");
+    for data in data {
+        mac.update(data);
+    }
+    let mut hash = mac.finalize().into_bytes();
+    let mut output = Zeroizing::new([0u8; 64]);
+    output.copy_from_slice(&hash);
+    hash.as_mut_slice().zeroize();
+    output
+}
+
+// Indexes of the path, which must all be hardened since Ed25519 keys only have hardened children.
+fn hardened_indexes(path: &BIP32Path) -> Result<Vec<u32>> {
+    let path = path.to_string();
+    path.split('/')
+        .skip(1)
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .strip_suffix('\'')
+                .and_then(|index| index.parse::<u32>().ok())
+                .filter(|index| *index < HARDEND)
+                .map(|index| index + HARDEND)
+                .ok_or_else(|| Error::Signer(format!("can't derive the key of path {}", path)))
+        })
+        .collect()
+}
+
+impl std::fmt::Debug for ZeroizingSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZeroizingSeed").finish()
+    }
+}
+
+impl Signer for ZeroizingSeed {
+    fn public_key<'a>(&'a self, path: &'a BIP32Path) -> SignerFuture<'a, [u8; 32]> {
+        Box::pin(async move { Ok(PublicKey::from(&secret_key(&self.bytes, path)?).to_bytes()) })
+    }
+
+    fn public_keys<'a>(&'a self, paths: &'a [BIP32Path]) -> SignerFuture<'a, Vec<[u8; 32]>> {
+        // A range of addresses takes a while to derive, so it's split in a chunk per CPU, each derived on a blocking
+        // thread with a copy of the seed which is cleared as well.
+        Box::pin(async move {
+            let chunk_size = ((paths.len() + num_cpus::get() - 1) / num_cpus::get()).max(1);
+            let chunks = paths.chunks(chunk_size).map(|chunk| {
+                let seed = Zeroizing::new(self.bytes.to_vec());
+                let chunk = chunk.to_vec();
+                tokio::task::spawn_blocking(move || {
+                    chunk
+                        .iter()
+                        .map(|path| Ok(PublicKey::from(&secret_key(&seed, path)?).to_bytes()))
+                        .collect::<Result<Vec<[u8; 32]>>>()
+                })
+            });
+            let mut public_keys = Vec::with_capacity(paths.len());
+            for chunk in try_join_all(chunks)
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+            {
+                public_keys.extend(chunk?);
+            }
+            Ok(public_keys)
+        })
+    }
+
+    fn sign<'a>(&'a self, path: &'a BIP32Path, essence: &'a [u8]) -> SignerFuture<'a, [u8; 64]> {
+        Box::pin(async move {
+            let secret_key = secret_key(&self.bytes, path)?;
+            let public_key = PublicKey::from(&secret_key);
+            Ok(ExpandedSecretKey::from(&secret_key)
+                .sign(essence, &public_key)
+                .to_bytes())
+        })
+    }
+}
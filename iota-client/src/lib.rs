@@ -9,18 +9,27 @@
 #[macro_use]
 extern crate serde;
 
+pub mod address;
 pub mod api;
 pub mod builder;
 pub mod client;
+pub mod encryption;
 pub mod error;
+pub mod keystore;
+pub mod mnemonic;
 pub mod node;
+pub mod pow;
+pub mod signing;
 pub mod types;
 
+pub use address::{parse_address, Bech32Address};
 pub use bee_signing_ext::{binary::BIP32Path, Seed};
 pub use builder::ClientBuilder;
 pub use client::{BrokerOptions, Client, ClientMiner, Topic, TopicEvent};
 pub use error::*;
+pub use pow::{NonceProvider, PayloadKind, PowHandle, PowProgress, RemotePowProvider};
 pub use reqwest::Url;
+pub use signing::{RemoteSigner, Signer, SignerFuture};
 pub use types::*;
 
 /// match a response with an expected status code or return the default error variant.
@@ -0,0 +1,43 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! BIP39 mnemonics to create and recover seeds.
+//!
+//! Like the other IOTA wallets, the seed is the 64 bytes BIP39 seed of the mnemonic and the optional passphrase, from
+//! which the keys of the addresses are derived with SLIP-0010.
+
+use crate::{keystore::ZeroizingSeed, Error, Result};
+
+use bip39::{Language, Mnemonic, MnemonicType};
+use zeroize::Zeroizing;
+
+/// Generate a new random mnemonic of 24 english words.
+pub fn generate_mnemonic() -> String {
+    Mnemonic::new(MnemonicType::Words24, Language::English).into_phrase()
+}
+
+/// Check that the words of the mnemonic are in the english wordlist and that its checksum is valid.
+pub fn verify_mnemonic(mnemonic: &str) -> Result<()> {
+    Mnemonic::validate(&normalize(mnemonic), Language::English).map_err(|e| Error::Mnemonic(e.to_string()))
+}
+
+/// Get the BIP39 seed bytes of the mnemonic and the passphrase, e.g. to store them in a keystore.
+pub fn mnemonic_to_seed_bytes(mnemonic: &str, passphrase: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let mnemonic =
+        Mnemonic::from_phrase(&normalize(mnemonic), Language::English).map_err(|e| Error::Mnemonic(e.to_string()))?;
+    Ok(Zeroizing::new(
+        bip39::Seed::new(&mnemonic, passphrase).as_bytes().to_vec(),
+    ))
+}
+
+/// Get the seed of the mnemonic and the passphrase, which can be used with `find_addresses`, `get_balance` and `send`.
+/// The passphrase is empty if there's none. The keys are derived from all the 64 bytes of the BIP39 seed, which are
+/// cleared from memory when the seed is dropped.
+pub fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<ZeroizingSeed> {
+    ZeroizingSeed::from_bytes(&mnemonic_to_seed_bytes(mnemonic, passphrase)?)
+}
+
+// Words separated by any whitespace, e.g. when pasted from a file
+fn normalize(mnemonic: &str) -> String {
+    mnemonic.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
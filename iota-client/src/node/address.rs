@@ -10,12 +10,22 @@ use std::convert::TryInto;
 /// Builder of GET /api/v1/address/{address} endpoint
 pub struct GetAddressBuilder<'a> {
     client: &'a Client,
+    include_spent: bool,
 }
 
 impl<'a> GetAddressBuilder<'a> {
     /// Create GET /api/v1/address/{address} endpoint builder
     pub fn new(client: &'a Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            include_spent: false,
+        }
+    }
+
+    /// Also get the spent outputs of the address, i.e. its whole history
+    pub fn include_spent(mut self, include_spent: bool) -> Self {
+        self.include_spent = include_spent;
+        self
     }
 
     /// Consume the builder and get the balance of a given address.
@@ -40,22 +50,29 @@ impl<'a> GetAddressBuilder<'a> {
         let address = address.to_bech32();
         let mut url = self.client.get_node()?;
         url.set_path(&format!("api/v1/addresses/{}/outputs", address));
+        if self.include_spent {
+            url.set_query(Some("include-spent=true"));
+        }
         let resp = reqwest::get(url).await?;
 
         parse_response!(resp, 200 => {
             let r = resp.json::<Response<AddressOutputs>>().await?.data.output_ids;
-            r.iter()
-                .map(|s| {
-                    let mut transaction_id = [0u8; 32];
-                    hex::decode_to_slice(&s[..64], &mut transaction_id)?;
-                    let index = u16::from_le_bytes(
-                        hex::decode(&s[64..]).map_err(|_| Error::InvalidParameter("index".to_string()))?[..]
-                            .try_into()
-                            .map_err(|_| Error::InvalidParameter("index".to_string()))?,
-                    );
-                    Ok(UTXOInput::new(TransactionId::new(transaction_id), index)?)
-                })
-                .collect::<Result<Box<[UTXOInput]>>>()
+            r.iter().map(|s| parse_output_id(s)).collect::<Result<Box<[UTXOInput]>>>()
         })
     }
 }
+
+/// Parse a hex encoded output id, the transaction id followed by the little endian output index.
+pub(crate) fn parse_output_id(s: &str) -> Result<UTXOInput> {
+    if s.len() != 68 || !s.is_ascii() {
+        return Err(Error::InvalidParameter(format!("output id {}", s)));
+    }
+    let mut transaction_id = [0u8; 32];
+    hex::decode_to_slice(&s[..64], &mut transaction_id)?;
+    let index = u16::from_le_bytes(
+        hex::decode(&s[64..]).map_err(|_| Error::InvalidParameter("index".to_string()))?[..]
+            .try_into()
+            .map_err(|_| Error::InvalidParameter("index".to_string()))?,
+    );
+    Ok(UTXOInput::new(TransactionId::new(transaction_id), index)?)
+}
@@ -7,8 +7,6 @@ use crate::{
 
 use bee_message::{Message, MessageId};
 
-use std::convert::TryInto;
-
 /// Builder of GET /api/v1/messages/{messageId} endpoint
 pub struct GetMessageBuilder<'a> {
     client: &'a Client,
@@ -21,11 +19,13 @@ impl<'a> GetMessageBuilder<'a> {
     }
 
     /// GET /api/v1/messages?index={Index} endpoint
-    /// Consume the builder and search for messages matching the index
-    pub async fn index(self, index: &str) -> Result<Box<[MessageId]>> {
+    /// Consume the builder and search for messages matching the index. The index is given as raw bytes and is
+    /// hex-encoded for the query, so it may contain spaces or `&`. Indexation payloads store their index as a UTF-8
+    /// string, so an index which isn't valid UTF-8 never matches a message.
+    pub async fn index<I: AsRef<[u8]>>(self, index: I) -> Result<Box<[MessageId]>> {
         let mut url = self.client.get_node()?;
         url.set_path("api/v1/messages");
-        url.set_query(Some(&format!("index={}", index)));
+        url.set_query(Some(&format!("index={}", hex::encode(index.as_ref()))));
         let resp = reqwest::get(url).await?;
 
         parse_response!(resp, 200 => {
@@ -51,7 +51,7 @@ impl<'a> GetMessageBuilder<'a> {
 
         parse_response!(resp, 200 => {
             let meta = resp.json::<Response<MessageJson>>().await?;
-            Ok(meta.data.try_into()?)
+            meta.data.into_message(self.client.get_min_pow_score().await?)
         })
     }
 
@@ -46,13 +46,14 @@ impl Topic {
         );
         let regexes = lazy_static!(
           [
-            Regex::new(r"messages/([A-Fa-f0-9]{64})/metadata").unwrap(),
-            Regex::new(r"outputs/([A-Fa-f0-9]{64})(\d{4})").unwrap(),
+            Regex::new(r"^messages/([A-Fa-f0-9]{64})/metadata$").unwrap(),
+            Regex::new(r"^outputs/([A-Fa-f0-9]{64})(\d{4})$").unwrap(),
             // bech32 address
-            Regex::new("addresses/(iota|atoi|iot|toi)1[A-Za-z0-9]+/outputs").unwrap(),
+            Regex::new("^addresses/(iota|atoi|iot|toi)1[A-Za-z0-9]+/outputs$").unwrap(),
             // ED25519 address hex
-            Regex::new("addresses/ed25519/([A-Fa-f0-9]{64})/outputs").unwrap(),
-            Regex::new(r"messages/indexation/(\.)").unwrap()
+            Regex::new("^addresses/ed25519/([A-Fa-f0-9]{64})/outputs$").unwrap(),
+            // hex encoded indexation key of 1 to 64 whole bytes, the whole topic must match so no other topic slips in
+            Regex::new(r"^messages/indexation/((?:[A-Fa-f0-9]{2}){1,64})$").unwrap()
           ].to_vec() => Vec<Regex>
         );
         let name = name.into();
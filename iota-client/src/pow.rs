@@ -0,0 +1,425 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Observable and cancellable proof-of-work, and the providers which can be plugged into the client.
+
+use crate::{Error, Result};
+
+use bee_message::prelude::Payload;
+use bee_pow::providers::{MinerCancel, Provider};
+use reqwest::Url;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+const LN_3: f64 = 1.098_612_288_668_109;
+
+/// Progress of a PoW job.
+#[derive(Debug, Clone)]
+pub struct PowProgress {
+    /// Time elapsed since the job started
+    pub elapsed: Duration,
+    /// Time elapsed since the current attempt started
+    pub attempt_elapsed: Duration,
+    /// Number of times the job was restarted with fresh tips
+    pub restarts: usize,
+    /// Target PoW score of the current attempt
+    pub target_score: f64,
+    /// Expected number of hashes needed to reach the target score
+    pub expected_hashes: f64,
+    /// Estimated hash rate in hashes per second, measured on previously completed PoW
+    pub hash_rate: Option<f64>,
+    /// Whether the job was cancelled
+    pub cancelled: bool,
+}
+
+impl PowProgress {
+    /// Estimated fraction, between 0 and 1, of the expected work done in the current attempt. PoW is a random search so
+    /// this is only an indication: the nonce may be found earlier or later.
+    pub fn fraction(&self) -> Option<f64> {
+        let hash_rate = self.hash_rate?;
+        if self.expected_hashes <= 0.0 {
+            return None;
+        }
+        Some((self.attempt_elapsed.as_secs_f64() * hash_rate / self.expected_hashes).min(1.0))
+    }
+}
+
+#[derive(Default)]
+struct PowStatus {
+    started: Option<Instant>,
+    attempt_started: Option<Instant>,
+    restarts: usize,
+    target_score: f64,
+    expected_hashes: f64,
+    hash_rate: Option<f64>,
+}
+
+// The cancel flag of the current attempt, created along with the attempt before its miner is started so an interrupt
+// is never missed, and its generation so the miner of an abandoned attempt doesn't pick up the flag of the next one.
+struct PowAttempt {
+    generation: u64,
+    cancel: MinerCancel,
+    stopped: bool,
+    // Error of the nonce provider, which the message builder doesn't pass on
+    failure: Option<String>,
+}
+
+impl Default for PowAttempt {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            cancel: MinerCancel::new(),
+            stopped: false,
+            failure: None,
+        }
+    }
+}
+
+impl PowAttempt {
+    fn stop(&mut self) {
+        self.stopped = true;
+        self.cancel.trigger();
+    }
+}
+
+struct PowState {
+    cancelled: AtomicBool,
+    attempt: Mutex<PowAttempt>,
+    status: Mutex<PowStatus>,
+}
+
+/// Handle of a PoW job, shared between the job and its observers. It can be used to cancel the job and to follow its
+/// progress from another task or thread.
+#[derive(Clone)]
+pub struct PowHandle {
+    state: Arc<PowState>,
+    // Generation of the attempt the handle was given to a provider for
+    generation: Option<u64>,
+}
+
+impl Default for PowHandle {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(PowState {
+                cancelled: AtomicBool::new(false),
+                attempt: Mutex::new(PowAttempt::default()),
+                status: Mutex::new(PowStatus::default()),
+            }),
+            generation: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for PowHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowHandle").field("progress", &self.progress()).finish()
+    }
+}
+
+impl PowHandle {
+    /// Create a new PoW handle
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Cancel the job. The workers stop as soon as possible and the job returns `Error::PowCancelled`.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.attempt.lock().unwrap().stop();
+    }
+
+    /// Whether the job was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Whether the attempt should be given up, because the job was cancelled or timed out, or is restarted with fresh
+    /// tips. A [`NonceProvider`] checks it regularly and returns as soon as it's set, its result is discarded anyway.
+    pub fn is_stopped(&self) -> bool {
+        let attempt = self.state.attempt.lock().unwrap();
+        attempt.stopped
+            || self
+                .generation
+                .map_or(false, |generation| generation != attempt.generation)
+    }
+
+    /// Get the progress of the job
+    pub fn progress(&self) -> PowProgress {
+        let status = self.state.status.lock().unwrap();
+        let since = |instant: Option<Instant>| instant.map(|i| i.elapsed()).unwrap_or_default();
+        PowProgress {
+            elapsed: since(status.started),
+            attempt_elapsed: since(status.attempt_started),
+            restarts: status.restarts,
+            target_score: status.target_score,
+            expected_hashes: status.expected_hashes,
+            hash_rate: status.hash_rate,
+            cancelled: self.is_cancelled(),
+        }
+    }
+
+    /// Mark the job as started, with a hash rate estimate from previous jobs.
+    pub(crate) fn start(&self, hash_rate: Option<f64>) {
+        let mut status = self.state.status.lock().unwrap();
+        status.started.get_or_insert_with(Instant::now);
+        if status.hash_rate.is_none() {
+            status.hash_rate = hash_rate;
+        }
+    }
+
+    /// Start a new attempt, abandoning the previous one, and get its generation.
+    pub(crate) fn new_attempt(&self) -> u64 {
+        let mut attempt = self.state.attempt.lock().unwrap();
+        attempt.stop();
+        attempt.generation += 1;
+        attempt.cancel = MinerCancel::new();
+        attempt.stopped = false;
+        attempt.failure = None;
+        if self.is_cancelled() {
+            attempt.stop();
+        }
+        attempt.generation
+    }
+
+    /// Stop the current attempt so it can be restarted with fresh tips. The miner stops even if it didn't start yet.
+    pub(crate) fn interrupt(&self) {
+        self.state.attempt.lock().unwrap().stop();
+    }
+
+    /// Get the handle of the attempt of the given generation, to be given to a provider.
+    pub(crate) fn for_attempt(&self, generation: u64) -> Self {
+        Self {
+            state: self.state.clone(),
+            generation: Some(generation),
+        }
+    }
+
+    /// Record that the job is restarted with fresh tips.
+    pub(crate) fn restart(&self) {
+        self.state.status.lock().unwrap().restarts += 1;
+    }
+
+    /// Start the PoW of the attempt of the given generation on a message of the given length and get the cancel flag
+    /// of the miner.
+    pub(crate) fn begin_attempt(&self, generation: u64, message_length: usize, target_score: f64) -> MinerCancel {
+        let cancel = {
+            let attempt = self.state.attempt.lock().unwrap();
+            if attempt.generation == generation {
+                attempt.cancel.clone()
+            } else {
+                // The attempt was abandoned before its miner started
+                let cancel = MinerCancel::new();
+                cancel.trigger();
+                cancel
+            }
+        };
+
+        let target_zeros = ((message_length as f64 * target_score).ln() / LN_3).ceil().max(0.0);
+        let mut status = self.state.status.lock().unwrap();
+        status.attempt_started = Some(Instant::now());
+        status.target_score = target_score;
+        status.expected_hashes = 3f64.powf(target_zeros);
+        cancel
+    }
+
+    /// Record the error of the nonce provider in the current attempt.
+    pub(crate) fn fail(&self, error: &Error) {
+        self.state.attempt.lock().unwrap().failure = Some(error.to_string());
+    }
+
+    /// Take the error of the nonce provider recorded in the current attempt, if any.
+    pub(crate) fn take_failure(&self) -> Option<String> {
+        self.state.attempt.lock().unwrap().failure.take()
+    }
+
+    /// Record the end of a successful PoW attempt to refine the hash rate estimate.
+    pub(crate) fn finish_attempt(&self) {
+        let mut status = self.state.status.lock().unwrap();
+        if let Some(attempt_started) = status.attempt_started {
+            let elapsed = attempt_started.elapsed().as_secs_f64();
+            if elapsed > 0.0 {
+                let sample = status.expected_hashes / elapsed;
+                // Smooth the estimate since a single PoW can be much luckier or unluckier than expected.
+                status.hash_rate = Some(match status.hash_rate {
+                    Some(rate) => (rate + sample) / 2.0,
+                    None => sample,
+                });
+            }
+        }
+    }
+
+    /// Get the hash rate estimate.
+    pub(crate) fn hash_rate(&self) -> Option<f64> {
+        self.state.status.lock().unwrap().hash_rate
+    }
+}
+
+/// A PoW provider which can be registered on the client with `ClientBuilder::pow_provider`. It's implemented for every
+/// `bee_pow` provider, so a custom CPU implementation or a dedicated PoW server can be used for the PoW of messages.
+pub trait NonceProvider: Send + Sync {
+    /// Find a nonce for the message bytes, which end with the 8 bytes of the nonce, reaching the target score. The
+    /// search should be given up once `handle.is_stopped()`, e.g. when the PoW is cancelled or times out.
+    fn nonce(&self, bytes: &[u8], target_score: f64, handle: &PowHandle) -> Result<u64>;
+}
+
+impl<P> NonceProvider for P
+where
+    P: Provider + Send + Sync,
+    P::Error: std::fmt::Display,
+{
+    // bee_pow providers can't be stopped from outside, so they always run to the end
+    fn nonce(&self, bytes: &[u8], target_score: f64, _handle: &PowHandle) -> Result<u64> {
+        Provider::nonce(self, bytes, target_score).map_err(|e| Error::Pow(e.to_string()))
+    }
+}
+
+/// Kind of message a PoW provider is selected for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PayloadKind {
+    /// Message without payload
+    Empty,
+    /// Message with a transaction payload
+    Transaction,
+    /// Message with an indexation payload
+    Indexation,
+    /// Message with a milestone payload
+    Milestone,
+    /// Message with another payload
+    Other,
+}
+
+impl PayloadKind {
+    /// Every kind of message
+    pub const ALL: [PayloadKind; 5] = [
+        Self::Empty,
+        Self::Transaction,
+        Self::Indexation,
+        Self::Milestone,
+        Self::Other,
+    ];
+}
+
+impl From<Option<&Payload>> for PayloadKind {
+    fn from(payload: Option<&Payload>) -> Self {
+        match payload {
+            None => Self::Empty,
+            Some(Payload::Transaction(_)) => Self::Transaction,
+            Some(Payload::Indexation(_)) => Self::Indexation,
+            Some(Payload::Milestone(_)) => Self::Milestone,
+            Some(_) => Self::Other,
+        }
+    }
+}
+
+/// PoW providers registered on the client, selected by the kind of message.
+#[derive(Clone, Default)]
+pub(crate) struct PowProviderRegistry {
+    default: Option<Arc<dyn NonceProvider>>,
+    by_kind: HashMap<PayloadKind, Arc<dyn NonceProvider>>,
+}
+
+impl PowProviderRegistry {
+    pub(crate) fn set_default(&mut self, provider: Arc<dyn NonceProvider>) {
+        self.default = Some(provider);
+    }
+
+    pub(crate) fn set(&mut self, kind: PayloadKind, provider: Arc<dyn NonceProvider>) {
+        self.by_kind.insert(kind, provider);
+    }
+
+    /// Whether a provider is used for every kind of message, either the default one or one set for the kind
+    pub(crate) fn covers_all_kinds(&self) -> bool {
+        PayloadKind::ALL.iter().all(|kind| self.get(*kind).is_some())
+    }
+
+    /// Get the provider of the kind of message, falling back to the default provider.
+    pub(crate) fn get(&self, kind: PayloadKind) -> Option<Arc<dyn NonceProvider>> {
+        self.by_kind.get(&kind).or_else(|| self.default.as_ref()).cloned()
+    }
+}
+
+impl std::fmt::Debug for PowProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowProviderRegistry")
+            .field("default", &self.default.is_some())
+            .field("kinds", &self.by_kind.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Request body of the remote PoW service.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemotePowRequest {
+    /// Hex encoded message bytes, ending with the 8 bytes of the nonce
+    pub bytes: String,
+    /// Target PoW score
+    #[serde(rename = "targetScore")]
+    pub target_score: f64,
+}
+
+/// Response body of the remote PoW service.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemotePowResponse {
+    /// Nonce as a decimal string, since it may not fit in a JSON number
+    pub nonce: String,
+}
+
+/// A provider delegating the PoW to a remote PoW service over HTTP.
+///
+/// The service receives a `POST` request on its URL with a JSON [`RemotePowRequest`] body and answers with status 200
+/// and a JSON [`RemotePowResponse`] body.
+#[derive(Debug, Clone)]
+pub struct RemotePowProvider {
+    url: Url,
+    timeout: Duration,
+}
+
+impl RemotePowProvider {
+    /// Create a provider for the PoW service at the given URL
+    pub fn new(url: &str) -> Result<Self> {
+        Ok(Self {
+            url: Url::parse(url).map_err(|_| Error::UrlError)?,
+            timeout: Duration::from_secs(60),
+        })
+    }
+
+    /// Set the time after which a request to the PoW service times out
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl NonceProvider for RemotePowProvider {
+    fn nonce(&self, bytes: &[u8], target_score: f64, handle: &PowHandle) -> Result<u64> {
+        if handle.is_stopped() {
+            return Err(Error::Pow(String::from("PoW stopped before the request")));
+        }
+        let request = RemotePowRequest {
+            bytes: hex::encode(bytes),
+            target_score,
+        };
+        let resp = reqwest::blocking::Client::new()
+            .post(self.url.clone())
+            .timeout(self.timeout)
+            .json(&request)
+            .send()?;
+
+        match resp.status().as_u16() {
+            200 => {
+                let nonce = resp.json::<RemotePowResponse>()?.nonce;
+                nonce
+                    .parse()
+                    .map_err(|_| Error::Pow(format!("invalid nonce {} from the PoW service", nonce)))
+            }
+            status => Err(Error::ResponseError(status, resp.text()?)),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signers deriving the keys of the addresses and signing transaction essences, so the high level APIs don't need
+//! access to the raw seed. The signers are asynchronous, so a signer waiting on a device or a service doesn't block
+//! the runtime.
+
+use crate::{Error, Result};
+
+use bee_signing_ext::{
+    binary::{BIP32Path, Ed25519PrivateKey},
+    Seed, Signer as _,
+};
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use futures::future::try_join_all;
+use rayon::prelude::*;
+
+use std::{
+    future::Future,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    pin::Pin,
+    time::Duration,
+};
+
+/// Length of an Ed25519 public key
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+/// Length of an Ed25519 signature
+pub const SIGNATURE_LENGTH: usize = 64;
+
+const FINGERPRINT_PATH: &str = "m/44'";
+
+/// Future returned by the methods of a [`Signer`].
+pub type SignerFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Holder of the Ed25519 keys of a seed, derived with SLIP-0010 on BIP32 paths.
+///
+/// The methods return futures, so a signer doing blocking I/O must run it off the runtime, e.g. with
+/// `tokio::task::spawn_blocking` like [`RemoteSigner`].
+pub trait Signer: Send + Sync {
+    /// Get the public key of the address at the path.
+    fn public_key<'a>(&'a self, path: &'a BIP32Path) -> SignerFuture<'a, [u8; PUBLIC_KEY_LENGTH]>;
+
+    /// Sign the transaction essence bytes with the key of the address at the path.
+    fn sign<'a>(&'a self, path: &'a BIP32Path, essence: &'a [u8]) -> SignerFuture<'a, [u8; SIGNATURE_LENGTH]>;
+
+    /// Get the public keys of the addresses at the paths, e.g. the ones of a range of addresses. By default, the keys
+    /// are requested concurrently, a signer deriving them in memory should derive the batch across several threads.
+    fn public_keys<'a>(&'a self, paths: &'a [BIP32Path]) -> SignerFuture<'a, Vec<[u8; PUBLIC_KEY_LENGTH]>> {
+        Box::pin(try_join_all(paths.iter().map(move |path| self.public_key(path))))
+    }
+
+    /// Get an identifier of the seed, used to cache the addresses derived from it. It's the hash of the public key of
+    /// the path `m/44'`, so it doesn't reveal any key of the addresses.
+    fn fingerprint(&self) -> SignerFuture<'_, [u8; 32]> {
+        Box::pin(async move {
+            let path = BIP32Path::from_str(FINGERPRINT_PATH).expect("invalid fingerprint path");
+            let mut hasher = VarBlake2b::new(32).unwrap();
+            hasher.update(self.public_key(&path).await?);
+            let mut fingerprint = [0u8; 32];
+            hasher.finalize_variable(|hash| fingerprint.copy_from_slice(hash));
+            Ok(fingerprint)
+        })
+    }
+}
+
+/// The in-memory seed signer.
+impl Signer for Seed {
+    fn public_key<'a>(&'a self, path: &'a BIP32Path) -> SignerFuture<'a, [u8; PUBLIC_KEY_LENGTH]> {
+        Box::pin(async move { Ok(private_key(self, path)?.generate_public_key().to_bytes()) })
+    }
+
+    fn public_keys<'a>(&'a self, paths: &'a [BIP32Path]) -> SignerFuture<'a, Vec<[u8; PUBLIC_KEY_LENGTH]>> {
+        // The seed can't be copied out of bee's `Seed` to a blocking thread, so the range is derived across the rayon
+        // thread pool while the calling thread waits for it.
+        Box::pin(async move {
+            paths
+                .par_iter()
+                .map(|path| Ok(private_key(self, path)?.generate_public_key().to_bytes()))
+                .collect()
+        })
+    }
+
+    fn sign<'a>(&'a self, path: &'a BIP32Path, essence: &'a [u8]) -> SignerFuture<'a, [u8; SIGNATURE_LENGTH]> {
+        Box::pin(async move { Ok(private_key(self, path)?.sign(essence).to_bytes()) })
+    }
+}
+
+fn private_key(seed: &Seed, path: &BIP32Path) -> Result<Ed25519PrivateKey> {
+    match seed {
+        Seed::Ed25519(s) => Ed25519PrivateKey::generate_from_seed(s, path)
+            .map_err(|_| Error::Signer(format!("can't derive the key of path {}", path.to_string()))),
+        _ => Err(Error::Signer(String::from("other seed scheme isn't supported"))),
+    }
+}
+
+/// Request of the remote signer protocol.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum RemoteSignerRequest {
+    /// Get the public key of the address at the path
+    PublicKey {
+        /// BIP32 path of the address
+        path: String,
+    },
+    /// Sign the essence with the key of the address at the path
+    Sign {
+        /// BIP32 path of the address
+        path: String,
+        /// Hex encoded transaction essence bytes
+        essence: String,
+    },
+}
+
+/// Response of the remote signer protocol.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteSignerResponse {
+    /// Hex encoded public key, answering a `publicKey` request
+    #[serde(rename = "publicKey", skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Hex encoded signature, answering a `sign` request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Reason why the request failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A signer delegating to a signing service listening on a local socket, e.g. in front of an HSM or an enclave.
+///
+/// For each request, the signer connects to the service and writes a JSON [`RemoteSignerRequest`] on a single line. The
+/// service answers with a JSON [`RemoteSignerResponse`] on a single line. The exchange runs on the blocking thread
+/// pool of the runtime, since the service may wait for a confirmation on a device.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    address: SocketAddr,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Create a signer for the service listening at the socket address
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the time after which a request to the service times out, e.g. when it waits for a confirmation on a device
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn request(&self, request: RemoteSignerRequest) -> Result<RemoteSignerResponse> {
+        let signer = self.clone();
+        tokio::task::spawn_blocking(move || signer.exchange(&request))
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+    }
+
+    fn exchange(&self, request: &RemoteSignerRequest) -> Result<RemoteSignerResponse> {
+        let mut stream = TcpStream::connect_timeout(&self.address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+        let response: RemoteSignerResponse = serde_json::from_str(&line)?;
+        match response.error {
+            Some(error) => Err(Error::Signer(error)),
+            None => Ok(response),
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key<'a>(&'a self, path: &'a BIP32Path) -> SignerFuture<'a, [u8; PUBLIC_KEY_LENGTH]> {
+        Box::pin(async move {
+            let response = self
+                .request(RemoteSignerRequest::PublicKey { path: path.to_string() })
+                .await?;
+            let mut public_key = [0u8; PUBLIC_KEY_LENGTH];
+            hex::decode_to_slice(
+                response
+                    .public_key
+                    .ok_or_else(|| Error::Signer(String::from("missing public key")))?,
+                &mut public_key,
+            )?;
+            Ok(public_key)
+        })
+    }
+
+    fn sign<'a>(&'a self, path: &'a BIP32Path, essence: &'a [u8]) -> SignerFuture<'a, [u8; SIGNATURE_LENGTH]> {
+        Box::pin(async move {
+            let response = self
+                .request(RemoteSignerRequest::Sign {
+                    path: path.to_string(),
+                    essence: hex::encode(essence),
+                })
+                .await?;
+            let mut signature = [0u8; SIGNATURE_LENGTH];
+            hex::decode_to_slice(
+                response
+                    .signature
+                    .ok_or_else(|| Error::Signer(String::from("missing signature")))?,
+                &mut signature,
+            )?;
+            Ok(signature)
+        })
+    }
+}
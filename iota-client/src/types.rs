@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Types of several IOTA APIs related objects
-use crate::Result;
+use crate::{client::hash_data, Result};
 
 use bee_message::{
     payload::milestone::{MilestoneEssence, MILESTONE_MERKLE_PROOF_LENGTH},
@@ -58,10 +58,49 @@ pub struct NodeInfo {
     pub pruning_index: usize,
     /// features
     pub features: Vec<String>,
+    /// minimum PoW score of the network
+    #[serde(rename = "minPowScore", default = "default_min_pow_score")]
+    pub min_pow_score: f64,
 }
 
 impl ResponseType for NodeInfo {}
 
+impl NodeInfo {
+    /// Whether the node does the PoW of the messages posted without nonce
+    pub fn has_remote_pow(&self) -> bool {
+        self.features.iter().any(|feature| feature.eq_ignore_ascii_case("pow"))
+    }
+}
+
+/// Default minimum PoW score of messages, used when the node doesn't provide the one of its network.
+pub const DEFAULT_POW_SCORE: f64 = 4000f64;
+
+fn default_min_pow_score() -> f64 {
+    DEFAULT_POW_SCORE
+}
+
+/// Network information cached by the client
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkInfo {
+    /// Network id, the first 8 bytes of the BLAKE2b-256 hash of the network name as a little endian integer
+    pub network_id: u64,
+    /// Network name
+    pub network_name: String,
+    /// Minimum PoW score of the network
+    pub min_pow_score: f64,
+}
+
+impl From<&NodeInfo> for NetworkInfo {
+    fn from(info: &NodeInfo) -> Self {
+        let hash = hash_data(info.network_id.as_bytes());
+        Self {
+            network_id: u64::from_le_bytes(hash[0..8].try_into().unwrap()),
+            network_name: info.network_id.clone(),
+            min_pow_score: info.min_pow_score,
+        }
+    }
+}
+
 /// Response of GET /api/v1/tips endpoint
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct Tips {
@@ -250,6 +289,29 @@ impl Transfers {
     }
 }
 
+/// Current version of the [`DataManifest`] format.
+pub const DATA_MANIFEST_VERSION: u8 = 1;
+
+/// Maximum amount of bytes of data stored in a single chunk of `publish_data()`. This leaves enough room in a message
+/// for the message header, the indexation key and the indexation payload header.
+pub const DATA_CHUNK_SIZE: usize = 30 * 1024;
+
+/// Manifest of data published with `publish_data()`.
+///
+/// It's stored as JSON in the data of an indexation payload and lists the chunk messages in the order their data has
+/// to be concatenated, along with the size and the BLAKE2b-256 hash of the whole data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataManifest {
+    /// Version of the manifest format
+    pub version: u8,
+    /// Total size of the data in bytes
+    pub size: usize,
+    /// Hex encoded BLAKE2b-256 hash of the data
+    pub hash: String,
+    /// Hex encoded message IDs of the chunks, in order
+    pub chunks: Vec<String>,
+}
+
 /// JSON struct for Message
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MessageJson {
@@ -278,16 +340,20 @@ impl From<&Message> for MessageJson {
     }
 }
 
-impl TryFrom<MessageJson> for Message {
-    type Error = crate::Error;
+impl MessageJson {
+    /// Remove the nonce, so the node computes it when the message is posted.
+    pub(crate) fn remove_nonce(&mut self) {
+        self.nonce.clear();
+    }
 
-    fn try_from(value: MessageJson) -> Result<Self> {
+    /// Convert the JSON message into a `Message` of a network with the given minimum PoW score.
+    pub fn into_message(self, min_pow_score: f64) -> Result<Message> {
         let mut parent1 = [0u8; 32];
-        hex::decode_to_slice(value.parent1, &mut parent1)?;
+        hex::decode_to_slice(self.parent1, &mut parent1)?;
         let mut parent2 = [0u8; 32];
-        hex::decode_to_slice(value.parent2, &mut parent2)?;
-        let nonce = value.nonce;
-        let network_id = value.network_id;
+        hex::decode_to_slice(self.parent2, &mut parent2)?;
+        let nonce = self.nonce;
+        let network_id = self.network_id;
         let parent1 = MessageId::new(parent1);
         let parent2 = MessageId::new(parent2);
         Ok(MessageBuilder::<Constant>::new()
@@ -298,7 +364,7 @@ impl TryFrom<MessageJson> for Message {
             )
             .with_parent1(parent1)
             .with_parent2(parent2)
-            .with_payload(get_payload_from_json(value.payload, Some((parent1, parent2)))?)
+            .with_payload(get_payload_from_json(self.payload, Some((parent1, parent2)))?)
             .with_nonce_provider(
                 ConstantBuilder::new()
                     .with_value(
@@ -307,7 +373,7 @@ impl TryFrom<MessageJson> for Message {
                             .map_err(|_| crate::Error::InvalidParameter(format!("nonce {}", nonce)))?,
                     )
                     .finish(),
-                4000f64,
+                min_pow_score,
             )
             .finish()?)
     }
@@ -381,7 +447,7 @@ impl From<&Payload> for PayloadJson {
     }
 }
 
-fn get_payload_from_json(payload: PayloadJson, tips: Option<(MessageId, MessageId)>) -> Result<Payload> {
+pub(crate) fn get_payload_from_json(payload: PayloadJson, tips: Option<(MessageId, MessageId)>) -> Result<Payload> {
     match payload {
         PayloadJson::Transaction(transaction_payload) => {
             let mut transaction = Transaction::builder();
@@ -0,0 +1,31 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{
+    api::{Account, IOTA_COIN_TYPE},
+    BIP32Path,
+};
+
+use std::str::FromStr;
+
+fn path(path: &str) -> String {
+    BIP32Path::from_str(path).unwrap().to_string()
+}
+
+#[test]
+fn test_account_paths() {
+    let account = Account::new(3);
+    assert_eq!(account.coin_type(), IOTA_COIN_TYPE);
+    assert_eq!(account.path().to_string(), path("m/44'/4218'/3'"));
+    assert_eq!(account.chain_path(false).to_string(), path("m/44'/4218'/3'/0'"));
+    assert_eq!(account.chain_path(true).to_string(), path("m/44'/4218'/3'/1'"));
+    assert_eq!(account.address_path(false, 0).to_string(), path("m/44'/4218'/3'/0'/0'"));
+    assert_eq!(account.address_path(true, 7).to_string(), path("m/44'/4218'/3'/1'/7'"));
+}
+
+#[test]
+fn test_account_coin_type() {
+    let account = Account::default().with_coin_type(1);
+    assert_eq!(account.index(), 0);
+    assert_eq!(account.address_path(false, 2).to_string(), path("m/44'/1'/0'/0'/2'"));
+}
@@ -0,0 +1,73 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{builder::Network, parse_address, Bech32Address, Error};
+
+use bee_message::prelude::{Address, Ed25519Address};
+
+const ED25519_ADDRESS: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const MAINNET_ADDRESS: &str = "iota1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj430ldu";
+const TESTNET_ADDRESS: &str = "atoi1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryjjl77h3";
+
+fn ed25519_address() -> Address {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(ED25519_ADDRESS, &mut bytes).unwrap();
+    Address::Ed25519(Ed25519Address::new(bytes))
+}
+
+#[test]
+fn test_try_from_bech32() {
+    for address in &[
+        MAINNET_ADDRESS,
+        TESTNET_ADDRESS,
+        "iot1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj3ad7xs",
+        "toi1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryjtuaxrx",
+    ] {
+        assert_eq!(Address::try_from_bech32(address).unwrap(), ed25519_address());
+    }
+    assert_eq!(
+        Address::try_from_bech32(&ed25519_address().to_bech32()).unwrap(),
+        ed25519_address()
+    );
+}
+
+#[test]
+fn test_try_from_bech32_for_network() {
+    assert!(Address::try_from_bech32_for_network(MAINNET_ADDRESS, &Network::Mainnet).is_ok());
+    assert!(Address::try_from_bech32_for_network(TESTNET_ADDRESS, &Network::Devnet).is_ok());
+    assert!(Address::try_from_bech32_for_network(TESTNET_ADDRESS, &Network::Comnet).is_ok());
+    match Address::try_from_bech32_for_network(TESTNET_ADDRESS, &Network::Mainnet) {
+        Err(Error::Bech32HrpMismatch(hrp, Network::Mainnet)) => assert_eq!(hrp, "atoi"),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_invalid_bech32() {
+    match Address::try_from_bech32("abcd1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryjwrkskk") {
+        Err(Error::UnknownBech32Hrp(hrp)) => assert_eq!(hrp, "abcd"),
+        r => panic!("unexpected result {:?}", r),
+    }
+    // Wrong checksum
+    match Address::try_from_bech32("iota1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj430ldq") {
+        Err(Error::Bech32(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    // Unknown address type
+    match Address::try_from_bech32("iota1q9f0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj0w6qwt") {
+        Err(Error::InvalidAddress(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    // Address of 31 bytes
+    match Address::try_from_bech32("iota1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utrq4p8dug") {
+        Err(Error::InvalidAddress(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_parse_address() {
+    assert_eq!(parse_address(ED25519_ADDRESS).unwrap(), ed25519_address());
+    assert_eq!(parse_address(MAINNET_ADDRESS).unwrap(), ed25519_address());
+    assert!(parse_address(&ED25519_ADDRESS[2..]).is_err());
+}
@@ -0,0 +1,123 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{BIP32Path, Client, Seed, Signer, SignerFuture};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Seed signer counting the derived public keys
+struct CountingSigner {
+    seed: Seed,
+    derivations: AtomicUsize,
+}
+
+impl CountingSigner {
+    fn new(byte: u8) -> Self {
+        Self {
+            seed: Seed::from_ed25519_bytes(&[byte; 32]).unwrap(),
+            derivations: AtomicUsize::new(0),
+        }
+    }
+
+    fn derivations(&self) -> usize {
+        self.derivations.swap(0, Ordering::SeqCst)
+    }
+}
+
+impl Signer for CountingSigner {
+    fn public_key<'a>(&'a self, path: &'a BIP32Path) -> SignerFuture<'a, [u8; 32]> {
+        self.derivations.fetch_add(1, Ordering::SeqCst);
+        self.seed.public_key(path)
+    }
+
+    fn sign<'a>(&'a self, path: &'a BIP32Path, essence: &'a [u8]) -> SignerFuture<'a, [u8; 64]> {
+        self.seed.sign(path, essence)
+    }
+}
+
+fn client(address_cache_size: usize) -> Client {
+    Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .address_cache_size(address_cache_size)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_address_cache() {
+    let client = client(100);
+    let signer = CountingSigner::new(1);
+    let path = BIP32Path::from_str("m/0'/0'").unwrap();
+
+    let addresses = client
+        .find_addresses(&signer)
+        .path(&path)
+        .range(0..10)
+        .get()
+        .await
+        .unwrap();
+    // The fingerprint of the seed is derived too
+    assert_eq!(signer.derivations(), 11);
+
+    // Only the addresses missing from the cache are derived, in order
+    let overlapping = client
+        .find_addresses(&signer)
+        .path(&path)
+        .range(5..15)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(signer.derivations(), 1 + 5);
+    assert_eq!(&overlapping[..5], &addresses[5..]);
+
+    // Another seed doesn't get the addresses of the first one
+    let other = CountingSigner::new(2);
+    let other_addresses = client
+        .find_addresses(&other)
+        .path(&path)
+        .range(0..10)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(other.derivations(), 11);
+    assert_ne!(other_addresses, addresses);
+
+    client.clear_address_cache();
+    assert_eq!(
+        client
+            .find_addresses(&signer)
+            .path(&path)
+            .range(0..10)
+            .get()
+            .await
+            .unwrap(),
+        addresses
+    );
+    assert_eq!(signer.derivations(), 11);
+}
+
+#[tokio::test]
+async fn test_address_cache_disabled() {
+    let client = client(0);
+    let signer = CountingSigner::new(1);
+    let path = BIP32Path::from_str("m/0'/0'").unwrap();
+    let first = client
+        .find_addresses(&signer)
+        .path(&path)
+        .range(0..20)
+        .get()
+        .await
+        .unwrap();
+
+    // Without cache, the same addresses are derived again
+    let second = client
+        .find_addresses(&signer)
+        .path(&path)
+        .range(0..20)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(signer.derivations(), 40);
+    assert_eq!(second, first);
+}
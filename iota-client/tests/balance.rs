@@ -0,0 +1,47 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{FakeNode, FakeOutput};
+use iota_client::{BIP32Path, Client, Seed};
+
+use std::str::FromStr;
+
+#[tokio::test]
+async fn test_balance_report_unspent_outputs() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str("m/0'/0'").unwrap();
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let addresses = client
+        .find_addresses(&seed)
+        .path(&path)
+        .range(0..2)
+        .get()
+        .await
+        .unwrap();
+    // The second address was emptied
+    let spent = vec![
+        FakeOutput::new(1, 0, &addresses[0], 10).spent(),
+        FakeOutput::new(2, 0, &addresses[1], 3).spent(),
+    ];
+    let unspent = FakeOutput::new(3, 1, &addresses[0], 5);
+    *ledger.outputs.lock().unwrap() = spent.iter().cloned().chain(Some(unspent.clone())).collect();
+
+    let report = client.get_balance(&seed).path(&path).get_report().await.unwrap();
+    assert_eq!(report.total_balance, 5);
+    assert_eq!(report.total_outputs, 1);
+    assert_eq!(report.addresses.len(), 2);
+    assert_eq!(report.addresses[0].balance, 5);
+    assert_eq!(report.addresses[0].outputs.len(), 1);
+    assert_eq!(report.addresses[0].outputs[0].amount, 5);
+    assert_eq!(report.addresses[1].balance, 0);
+    assert!(report.addresses[1].outputs.is_empty());
+
+    // The spent outputs aren't fetched
+    for output in &spent {
+        assert_eq!(node.requests(&format!("/api/v1/outputs/{}", output.id())), 0);
+    }
+    assert_eq!(node.requests(&format!("/api/v1/outputs/{}", unspent.id())), 1);
+}
@@ -0,0 +1,278 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{FakeNode, FakeOutput};
+use iota_client::{
+    api::{payouts_from_csv, payouts_from_json, Payout, PayoutResult, PayoutStatus},
+    parse_address, BIP32Path, Client, Error, Seed,
+};
+
+use std::str::FromStr;
+
+const MAINNET_ADDRESS: &str = "iota1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj430ldu";
+const ED25519_ADDRESS: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const PATH: &str = "m/44'/4218'/0'/0'";
+const TRANSACTION_ID: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+#[test]
+fn test_payouts_from_csv() {
+    let csv = format!(
+        "address,amount\n{},1000000\n\n {} , 42 \n",
+        MAINNET_ADDRESS, ED25519_ADDRESS
+    );
+    assert_eq!(
+        payouts_from_csv(&csv).unwrap(),
+        vec![
+            Payout {
+                address: MAINNET_ADDRESS.to_string(),
+                amount: 1_000_000,
+            },
+            Payout {
+                address: ED25519_ADDRESS.to_string(),
+                amount: 42,
+            },
+        ]
+    );
+
+    // Without header
+    assert_eq!(payouts_from_csv(&format!("{},1", MAINNET_ADDRESS)).unwrap().len(), 1);
+}
+
+#[test]
+fn test_payouts_from_csv_invalid() {
+    for csv in &[
+        format!("{},1\n{},many", MAINNET_ADDRESS, MAINNET_ADDRESS),
+        format!("{},1,2", MAINNET_ADDRESS),
+        format!("{},0", MAINNET_ADDRESS),
+        String::from("iota1invalid,1"),
+    ] {
+        assert!(payouts_from_csv(csv).is_err(), "{}", csv);
+    }
+    match payouts_from_csv(&format!("{},0", MAINNET_ADDRESS)) {
+        Err(Error::InvalidParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_payouts_from_json() {
+    let json = format!(r#"[{{"address": "{}", "amount": 5}}]"#, MAINNET_ADDRESS);
+    assert_eq!(
+        payouts_from_json(&json).unwrap(),
+        vec![Payout {
+            address: MAINNET_ADDRESS.to_string(),
+            amount: 5,
+        }]
+    );
+    assert!(payouts_from_json(&format!(r#"[{{"address": "{}", "amount": 0}}]"#, MAINNET_ADDRESS)).is_err());
+    assert!(payouts_from_json(r#"[{"address": "iota1invalid", "amount": 5}]"#).is_err());
+}
+
+#[test]
+fn test_payout_result_serde() {
+    let message_id = "a008ce3354591950232c0dacdfcb17c4f6457c5bf407eff1befaab5fa7b59b7a";
+    let results = vec![
+        PayoutResult {
+            payout: Payout {
+                address: MAINNET_ADDRESS.to_string(),
+                amount: 1,
+            },
+            status: PayoutStatus::Pending,
+        },
+        PayoutResult {
+            payout: Payout {
+                address: MAINNET_ADDRESS.to_string(),
+                amount: 2,
+            },
+            status: PayoutStatus::Submitted {
+                message_id: message_id.to_string(),
+            },
+        },
+        PayoutResult {
+            payout: Payout {
+                address: MAINNET_ADDRESS.to_string(),
+                amount: 3,
+            },
+            status: PayoutStatus::Failed {
+                error: String::from("not enough balance"),
+            },
+        },
+        PayoutResult {
+            payout: Payout {
+                address: MAINNET_ADDRESS.to_string(),
+                amount: 4,
+            },
+            status: PayoutStatus::Unknown {
+                transaction_id: TRANSACTION_ID.to_string(),
+                inputs: vec![format!("{}0000", TRANSACTION_ID)],
+                error: String::from("connection closed"),
+            },
+        },
+    ];
+
+    let json = serde_json::to_value(&results).unwrap();
+    assert_eq!(
+        json[1],
+        serde_json::json!({
+            "address": MAINNET_ADDRESS,
+            "amount": 2,
+            "status": "submitted",
+            "messageId": message_id,
+        })
+    );
+    assert_eq!(json[0]["status"], "pending");
+    assert_eq!(json[2]["error"], "not enough balance");
+    assert_eq!(
+        json[3],
+        serde_json::json!({
+            "address": MAINNET_ADDRESS,
+            "amount": 4,
+            "status": "unknown",
+            "transactionId": TRANSACTION_ID,
+            "inputs": [format!("{}0000", TRANSACTION_ID)],
+            "error": "connection closed",
+        })
+    );
+
+    let deserialized: Vec<PayoutResult> = serde_json::from_value(json).unwrap();
+    assert_eq!(deserialized, results);
+}
+
+#[tokio::test]
+async fn test_resume_unknown_payouts() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let address = parse_address(ED25519_ADDRESS).unwrap();
+    let input = FakeOutput::new(1, 0, &address, 10);
+    let unknown = vec![PayoutResult {
+        payout: Payout {
+            address: MAINNET_ADDRESS.to_string(),
+            amount: 4,
+        },
+        status: PayoutStatus::Unknown {
+            transaction_id: format!("{:064x}", 2),
+            inputs: vec![input.id()],
+            error: String::from("connection closed"),
+        },
+    }];
+
+    // The input isn't spent, so the transaction may still be included and the batch stops there
+    let (node, ledger) = FakeNode::with_ledger(vec![input.clone()]);
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let (results, error) = client
+        .batch_send(&seed)
+        .path(&path)
+        .resume(unknown.clone())
+        .post()
+        .await;
+    match error {
+        Some(Error::TransactionUnresolved(_)) => {}
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(results, unknown);
+
+    // The transaction is included, so its outputs exist
+    *ledger.outputs.lock().unwrap() = vec![input.clone().spent(), FakeOutput::new(2, 0, &address, 4)];
+    let (results, error) = client
+        .batch_send(&seed)
+        .path(&path)
+        .resume(unknown.clone())
+        .post()
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+    assert_eq!(
+        results[0].status,
+        PayoutStatus::Confirmed {
+            message_id: format!("{:064x}", 3),
+        }
+    );
+
+    // Another transaction spent the input, so the payout is sent again, which fails on the empty seed
+    *ledger.outputs.lock().unwrap() = vec![input.spent()];
+    let (results, error) = client.batch_send(&seed).path(&path).resume(unknown).post().await;
+    assert!(error.is_some());
+    assert!(matches!(results[0].status, PayoutStatus::Failed { .. }));
+    assert!(ledger.messages.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_batch_send_payouts_and_resume() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let payout = Payout {
+        address: MAINNET_ADDRESS.to_string(),
+        amount: 4,
+    };
+    let resumed = vec![PayoutResult {
+        payout: payout.clone(),
+        status: PayoutStatus::Pending,
+    }];
+
+    // Giving the payouts along with the results holding them would pay them twice
+    let (results, error) = client
+        .batch_send(&seed)
+        .path(&path)
+        .payouts(vec![payout])
+        .resume(resumed.clone())
+        .post()
+        .await;
+    match error {
+        Some(Error::InvalidParameter(_)) => {}
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(results, resumed);
+    assert!(ledger.messages.lock().unwrap().is_empty());
+}
+
+/// Send a payout from a seed holding a single output, with the path of the node failing.
+async fn send_failing(path: &str) -> (Vec<PayoutResult>, Option<Error>, FakeOutput) {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let bip32_path = BIP32Path::from_str(PATH).unwrap();
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let addresses = client
+        .find_addresses(&seed)
+        .path(&bip32_path)
+        .range(0..1)
+        .get()
+        .await
+        .unwrap();
+    let input = FakeOutput::new(1, 0, &addresses[0], 10);
+    *ledger.outputs.lock().unwrap() = vec![input.clone()];
+    ledger.failing.lock().unwrap().push((path.to_string(), 500));
+
+    let (results, error) = client
+        .batch_send(&seed)
+        .path(&bip32_path)
+        .payouts(vec![Payout {
+            address: MAINNET_ADDRESS.to_string(),
+            amount: 4,
+        }])
+        .post()
+        .await;
+    assert!(ledger.messages.lock().unwrap().is_empty());
+    (results, error, input)
+}
+
+#[tokio::test]
+async fn test_batch_send_not_posted() {
+    // The message can't be built without tips, so it never reached the node
+    let (results, error, _) = send_failing("/api/v1/tips").await;
+    assert!(matches!(error, Some(Error::ResponseError(500, _))), "{:?}", error);
+    assert!(matches!(results[0].status, PayoutStatus::Failed { .. }));
+}
+
+#[tokio::test]
+async fn test_batch_send_post_unknown() {
+    // The node may have accepted the message before failing
+    let (results, error, input) = send_failing("/api/v1/messages").await;
+    assert!(matches!(error, Some(Error::ResponseError(500, _))), "{:?}", error);
+    match &results[0].status {
+        PayoutStatus::Unknown { inputs, .. } => assert_eq!(inputs, &vec![input.id()]),
+        status => panic!("unexpected status {:?}", status),
+    }
+}
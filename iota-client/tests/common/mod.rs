@@ -0,0 +1,309 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A node answering with canned responses on a local port, so the client can be tested offline.
+
+#![allow(dead_code)]
+
+use bee_message::prelude::{Address, UTXOInput};
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+pub const TIP1: &str = "a008ce3354591950232c0dacdfcb17c4f6457c5bf407eff1befaab5fa7b59b7a";
+pub const TIP2: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+pub const NETWORK: &str = "testnet";
+
+type Handler = dyn Fn(&str, &str, &[u8]) -> Option<(u16, String)> + Send + Sync;
+
+pub struct FakeNode {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeNode {
+    /// Start a node answering the health, info and tips endpoints
+    pub fn start() -> Self {
+        Self::with_handler(|_, _, _| None)
+    }
+
+    /// Start a node answering with the handler, or like `start()` for the requests the handler returns `None` for.
+    /// The handler gets the method, the path with its query and the body of the request.
+    pub fn with_handler<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &str, &[u8]) -> Option<(u16, String)> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let requests_ = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+                let requests = requests_.clone();
+                let handler = handler.clone();
+                thread::spawn(move || {
+                    let _ = serve(stream, &requests, &*handler);
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Start a node storing the posted messages, with the position of a message in the store as its id. The store is
+    /// returned to check or tamper with the messages.
+    pub fn storing() -> (Self, Arc<Mutex<Vec<String>>>) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let messages_ = messages.clone();
+        let node = Self::with_handler(move |method, path, body| {
+            let mut messages = messages_.lock().unwrap();
+            match (method, path) {
+                ("POST", "/api/v1/messages") => {
+                    messages.push(String::from_utf8(body.to_vec()).unwrap());
+                    Some((
+                        201,
+                        format!(r#"{{"data":{{"messageId":"{:064x}"}}}}"#, messages.len() - 1),
+                    ))
+                }
+                ("GET", path) if path.starts_with("/api/v1/messages/") && !path.ends_with("/metadata") => {
+                    let position = usize::from_str_radix(&path["/api/v1/messages/".len()..], 16).ok()?;
+                    messages
+                        .get(position)
+                        .map(|message| (200, format!(r#"{{"data":{}}}"#, message)))
+                }
+                _ => None,
+            }
+        });
+        (node, messages)
+    }
+
+    /// Start a node answering the address and output endpoints from the outputs of the ledger, and storing the posted
+    /// messages like `storing()`. The outputs can be changed while the node runs, and any path can be made to fail.
+    pub fn with_ledger(outputs: Vec<FakeOutput>) -> (Self, FakeLedger) {
+        let ledger = FakeLedger {
+            outputs: Arc::new(Mutex::new(outputs)),
+            messages: Arc::new(Mutex::new(Vec::new())),
+            failing: Arc::new(Mutex::new(Vec::new())),
+        };
+        let ledger_ = ledger.clone();
+        let node = Self::with_handler(move |method, target, body| {
+            let mut parts = target.splitn(2, '?');
+            let path = parts.next().unwrap();
+            let include_spent = parts.next() == Some("include-spent=true");
+            let failing = ledger_.failing.lock().unwrap();
+            if let Some((_, status)) = failing.iter().find(|(failing, _)| failing == path) {
+                return Some((
+                    *status,
+                    format!(r#"{{"error":{{"code":"{}","message":"failing"}}}}"#, status),
+                ));
+            }
+            let outputs = ledger_.outputs.lock().unwrap();
+            let mut messages = ledger_.messages.lock().unwrap();
+            match (method, path) {
+                ("POST", "/api/v1/messages") => {
+                    messages.push(String::from_utf8(body.to_vec()).unwrap());
+                    Some((
+                        201,
+                        format!(r#"{{"data":{{"messageId":"{:064x}"}}}}"#, messages.len() - 1),
+                    ))
+                }
+                ("GET", path) if path.starts_with("/api/v1/addresses/") => {
+                    let path = &path["/api/v1/addresses/".len()..];
+                    let (address, list) = match path.strip_suffix("/outputs") {
+                        Some(address) => (address, true),
+                        None => (path, false),
+                    };
+                    let owned = outputs
+                        .iter()
+                        .filter(|output| output.address.to_bech32() == address && (include_spent || !output.is_spent));
+                    let data = if list {
+                        let output_ids: Vec<String> = owned.map(FakeOutput::id).collect();
+                        serde_json::json!({ "address": address, "count": output_ids.len(), "outputIds": output_ids })
+                    } else {
+                        let owned: Vec<&FakeOutput> = owned.collect();
+                        let balance: u64 = owned.iter().map(|output| output.amount).sum();
+                        serde_json::json!({ "address": address, "count": owned.len(), "balance": balance })
+                    };
+                    Some((200, serde_json::json!({ "data": data }).to_string()))
+                }
+                ("GET", path) if path.starts_with("/api/v1/outputs/") => {
+                    let id = &path["/api/v1/outputs/".len()..];
+                    outputs.iter().find(|output| output.id() == id).map(|output| {
+                        let address = match &output.address {
+                            Address::Ed25519(address) => address.to_string(),
+                            _ => unreachable!(),
+                        };
+                        let data = serde_json::json!({
+                            "messageId": output.message_id,
+                            "transactionId": output.transaction_id,
+                            "outputIndex": output.index,
+                            "isSpent": output.is_spent,
+                            "output": {
+                                "type": 0,
+                                "address": { "type": 1, "address": address },
+                                "amount": output.amount,
+                            },
+                        });
+                        (200, serde_json::json!({ "data": data }).to_string())
+                    })
+                }
+                ("GET", path) if path.starts_with("/api/v1/messages/") && !path.ends_with("/metadata") => {
+                    let position = usize::from_str_radix(&path["/api/v1/messages/".len()..], 16).ok()?;
+                    messages
+                        .get(position)
+                        .map(|message| (200, format!(r#"{{"data":{}}}"#, message)))
+                }
+                _ => None,
+            }
+        });
+        (node, ledger)
+    }
+
+    /// Number of requests received on the path
+    pub fn requests(&self, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.as_str() == path)
+            .count()
+    }
+}
+
+/// An output of the ledger of a fake node
+#[derive(Debug, Clone)]
+pub struct FakeOutput {
+    pub message_id: String,
+    pub transaction_id: String,
+    pub index: u16,
+    pub address: Address,
+    pub amount: u64,
+    pub is_spent: bool,
+}
+
+impl FakeOutput {
+    /// Unspent output of the transaction with the given number
+    pub fn new(transaction: u64, index: u16, address: &Address, amount: u64) -> Self {
+        Self {
+            message_id: format!("{:064x}", transaction + 1),
+            transaction_id: format!("{:064x}", transaction),
+            index,
+            address: address.clone(),
+            amount,
+            is_spent: false,
+        }
+    }
+
+    /// Mark the output as spent
+    pub fn spent(mut self) -> Self {
+        self.is_spent = true;
+        self
+    }
+
+    /// Output id, the transaction id followed by the little endian index
+    pub fn id(&self) -> String {
+        format!("{}{}", self.transaction_id, hex::encode(self.index.to_le_bytes()))
+    }
+
+    /// Input spending the output
+    pub fn input(&self) -> UTXOInput {
+        UTXOInput::from_str(&self.id()).unwrap()
+    }
+}
+
+/// The state of a fake node started with `FakeNode::with_ledger()`
+#[derive(Clone)]
+pub struct FakeLedger {
+    pub outputs: Arc<Mutex<Vec<FakeOutput>>>,
+    pub messages: Arc<Mutex<Vec<String>>>,
+    /// Paths answered with an error status instead
+    pub failing: Arc<Mutex<Vec<(String, u16)>>>,
+}
+
+fn serve(stream: TcpStream, requests: &Mutex<Vec<String>>, handler: &Handler) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    // Connections are kept alive, so several requests can come on the same one
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+        let path = target.split('?').next().unwrap().to_string();
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(position) = header.find(':') {
+                if header[..position].eq_ignore_ascii_case("content-length") {
+                    content_length = header[position + 1..].trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        requests.lock().unwrap().push(path.clone());
+        let (status, body) = handler(&method, &target, &body).unwrap_or_else(|| default_response(&path));
+        write!(
+            writer,
+            "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        writer.flush()?;
+    }
+}
+
+/// Body of the info endpoint of a healthy node with the features
+pub fn info(features: &[&str]) -> String {
+    serde_json::json!({
+        "data": {
+            "name": "HORNET",
+            "version": "0.6.0",
+            "isHealthy": true,
+            "networkId": NETWORK,
+            "latestMilestoneIndex": 1,
+            "solidMilestoneIndex": 1,
+            "pruningIndex": 0,
+            "features": features,
+            "minPowScore": 4000,
+        }
+    })
+    .to_string()
+}
+
+fn default_response(path: &str) -> (u16, String) {
+    match path {
+        "/health" => (200, String::new()),
+        "/api/v1/info" => (200, info(&[])),
+        "/api/v1/tips" => (
+            200,
+            format!(
+                r#"{{"data":{{"tip1MessageId":"{}","tip2MessageId":"{}"}}}}"#,
+                TIP1, TIP2
+            ),
+        ),
+        _ => (404, String::from(r#"{"error":{"code":"404","message":"not found"}}"#)),
+    }
+}
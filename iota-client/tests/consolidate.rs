@@ -0,0 +1,71 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{FakeNode, FakeOutput};
+use iota_client::{
+    api::{sign_transaction, Account},
+    Client, Seed,
+};
+
+#[tokio::test]
+async fn test_prepare_consolidation() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let account = Account::new(0);
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let external = client
+        .find_addresses(&seed)
+        .account(account)
+        .range(0..2)
+        .get()
+        .await
+        .unwrap();
+    let internal = client
+        .find_addresses(&seed)
+        .account(account)
+        .internal(true)
+        .range(0..2)
+        .get()
+        .await
+        .unwrap();
+    let spent = FakeOutput::new(1, 0, &external[0], 5).spent();
+    *ledger.outputs.lock().unwrap() = vec![
+        spent.clone(),
+        FakeOutput::new(2, 0, &external[0], 1),
+        FakeOutput::new(2, 1, &external[0], 2),
+        FakeOutput::new(3, 0, &internal[0], 3),
+    ];
+
+    let prepared = client
+        .consolidate(&seed)
+        .account(account)
+        .max_inputs(2)
+        .prepare()
+        .await
+        .unwrap();
+
+    // The outputs go to the first unused address of the internal chain, at most two at a time
+    assert_eq!(prepared.len(), 2);
+    let target = internal[1].to_bech32();
+    for (transaction, (inputs, amount)) in prepared.iter().zip(vec![(2, 3), (1, 3)]) {
+        assert_eq!(transaction.inputs.len(), inputs);
+        assert_eq!(transaction.outputs.len(), 1);
+        assert_eq!(transaction.outputs[0].address, target);
+        assert_eq!(transaction.outputs[0].amount, amount);
+        assert!(!transaction.outputs[0].remainder);
+    }
+    for input in &prepared[0].inputs {
+        assert_eq!(input.address, external[0].to_bech32());
+        assert_eq!(input.path, account.address_path(false, 0).to_string());
+    }
+    assert_eq!(prepared[1].inputs[0].path, account.address_path(true, 0).to_string());
+    // The spent output isn't even fetched
+    assert_eq!(node.requests(&format!("/api/v1/outputs/{}", spent.id())), 0);
+
+    // The paths of the inputs match their address
+    for transaction in prepared {
+        sign_transaction(transaction, &seed).await.unwrap();
+    }
+}
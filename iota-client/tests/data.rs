@@ -0,0 +1,104 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use bee_message::prelude::Payload;
+use common::FakeNode;
+use iota_client::{Client, DataManifest, Error, DATA_CHUNK_SIZE, DATA_MANIFEST_VERSION};
+
+fn client(node: &FakeNode) -> Client {
+    Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .min_pow_score(1f64)
+        .build()
+        .unwrap()
+}
+
+fn data() -> Vec<u8> {
+    (0..2 * DATA_CHUNK_SIZE + 100)
+        .map(|i| (i / DATA_CHUNK_SIZE) as u8)
+        .collect()
+}
+
+#[test]
+fn test_data_manifest_json() {
+    let manifest = DataManifest {
+        version: DATA_MANIFEST_VERSION,
+        size: 3,
+        hash: "ab".repeat(32),
+        chunks: vec!["01".repeat(32)],
+    };
+    let json = serde_json::to_value(&manifest).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "version": 1,
+            "size": 3,
+            "hash": "ab".repeat(32),
+            "chunks": ["01".repeat(32)],
+        })
+    );
+    assert_eq!(serde_json::from_value::<DataManifest>(json).unwrap(), manifest);
+}
+
+#[tokio::test]
+async fn test_publish_fetch_data() {
+    let (node, messages) = FakeNode::storing();
+    let client = client(&node);
+
+    let manifest_id = client.publish_data("DATA", data()).await.unwrap();
+
+    // Three chunks and the manifest
+    assert_eq!(messages.lock().unwrap().len(), 4);
+    let manifest = client.get_message().data(&manifest_id).await.unwrap();
+    let manifest: DataManifest = match manifest.payload() {
+        Some(Payload::Indexation(indexation)) => serde_json::from_slice(indexation.data()).unwrap(),
+        payload => panic!("unexpected payload {:?}", payload),
+    };
+    assert_eq!(manifest.size, data().len());
+    assert_eq!(manifest.chunks.len(), 3);
+
+    assert_eq!(client.fetch_data(&manifest_id).await.unwrap(), data());
+}
+
+#[tokio::test]
+async fn test_fetch_data_hash_mismatch() {
+    let (node, messages) = FakeNode::storing();
+    let client = client(&node);
+    let manifest_id = client.publish_data("DATA", data()).await.unwrap();
+
+    // Chunks of the same size in the wrong order
+    messages.lock().unwrap().swap(0, 1);
+    match client.fetch_data(&manifest_id).await {
+        Err(Error::DataIntegrity(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_data_size_exceeds_chunks() {
+    let (node, _) = FakeNode::storing();
+    let client = client(&node);
+    let manifest = DataManifest {
+        version: DATA_MANIFEST_VERSION,
+        size: usize::MAX,
+        hash: "ab".repeat(32),
+        chunks: vec!["01".repeat(32)],
+    };
+    let manifest_id = client
+        .message()
+        .with_index("DATA")
+        .with_data(serde_json::to_vec(&manifest).unwrap())
+        .post()
+        .await
+        .unwrap();
+
+    match client.fetch_data(&manifest_id).await {
+        Err(Error::DataIntegrity(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    // The size is rejected before fetching the chunks
+    assert_eq!(node.requests(&format!("/api/v1/messages/{}", "01".repeat(32))), 0);
+}
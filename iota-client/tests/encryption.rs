@@ -0,0 +1,104 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use bee_message::prelude::{Indexation, MessageId, Payload};
+use common::FakeNode;
+use iota_client::{
+    encryption::{decrypt, decrypt_indexation, decrypt_message, encrypt, encrypt_indexation, generate_key_pair},
+    Client, Error,
+};
+
+use std::str::FromStr;
+
+// Version, ephemeral public key and recipient count
+const HEADER_LENGTH: usize = 34;
+// Key id, nonce and wrapped key
+const RECIPIENT_LENGTH: usize = 80;
+
+#[test]
+fn test_encrypt_for_multiple_recipients() {
+    let (alice_secret, alice_public) = generate_key_pair();
+    let (bob_secret, bob_public) = generate_key_pair();
+
+    let envelope = encrypt(b"supply chain data", &[alice_public, bob_public]).unwrap();
+
+    assert_eq!(decrypt(&envelope, &alice_secret).unwrap(), b"supply chain data");
+    assert_eq!(decrypt(&envelope, &bob_secret).unwrap(), b"supply chain data");
+}
+
+#[test]
+fn test_decrypt_with_other_key() {
+    let (_, alice_public) = generate_key_pair();
+    let (eve_secret, _) = generate_key_pair();
+
+    let envelope = encrypt(b"supply chain data", &[alice_public]).unwrap();
+
+    assert!(decrypt(&envelope, &eve_secret).is_err());
+}
+
+#[test]
+fn test_decrypt_tampered_envelope() {
+    let (alice_secret, alice_public) = generate_key_pair();
+
+    let mut envelope = encrypt(b"supply chain data", &[alice_public]).unwrap();
+    let last = envelope.len() - 1;
+    envelope[last] ^= 1;
+
+    assert!(decrypt(&envelope, &alice_secret).is_err());
+}
+
+#[test]
+fn test_encrypted_indexation() {
+    let (alice_secret, alice_public) = generate_key_pair();
+
+    let indexation = encrypt_indexation("Hello", b"Tangle", &[alice_public]).unwrap();
+
+    assert_ne!(indexation.data(), b"Tangle");
+    assert_eq!(decrypt_indexation(&indexation, &alice_secret).unwrap(), b"Tangle");
+}
+
+#[test]
+fn test_decrypt_removed_recipient() {
+    let (alice_secret, alice_public) = generate_key_pair();
+    let (_, bob_public) = generate_key_pair();
+
+    let mut envelope = encrypt(b"supply chain data", &[alice_public, bob_public]).unwrap();
+    // Drop the second recipient, the wrapped key of the first one is left untouched
+    envelope[HEADER_LENGTH - 1] = 1;
+    envelope.drain(HEADER_LENGTH + RECIPIENT_LENGTH..HEADER_LENGTH + 2 * RECIPIENT_LENGTH);
+
+    assert!(decrypt(&envelope, &alice_secret).is_err());
+}
+
+#[tokio::test]
+async fn test_encrypted_message() {
+    let node = FakeNode::start();
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let (alice_secret, alice_public) = generate_key_pair();
+    let parent = MessageId::from_str(common::TIP1).unwrap();
+
+    let message = client
+        .message()
+        .with_parents(parent, parent)
+        .with_index("Hello")
+        .with_data(b"Tangle".to_vec())
+        .with_encryption(&[alice_public])
+        .finish()
+        .await
+        .unwrap();
+    assert_eq!(decrypt_message(&message, &alice_secret).unwrap(), b"Tangle");
+
+    let indexation = Payload::Indexation(Box::new(Indexation::new(String::from("Hello"), b"Tangle").unwrap()));
+    match client
+        .message()
+        .with_payload(indexation)
+        .with_encryption(&[alice_public])
+        .finish()
+        .await
+    {
+        Err(Error::InvalidParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{FakeNode, FakeOutput};
+use iota_client::{
+    api::{classify_transaction, TransactionDirection, TransactionHistory, TransactionRecord},
+    parse_address, Client,
+};
+
+const MESSAGE_ID: &str = "a008ce3354591950232c0dacdfcb17c4f6457c5bf407eff1befaab5fa7b59b7a";
+const TRANSACTION_ID: &str = "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649";
+const SENDER: &str = "iota1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryj430ldu";
+const RECIPIENT: &str = "atoi1qpf0mlq8yxpx2nck8a0slxnzr4ef2ek8f5gqxlzd0wasgp73utryjjl77h3";
+
+fn history() -> TransactionHistory {
+    TransactionHistory {
+        transactions: vec![
+            TransactionRecord {
+                message_id: "0".repeat(64),
+                transaction_id: "0".repeat(64),
+                direction: TransactionDirection::Incoming,
+                amount: 1_000,
+                counterparties: Vec::new(),
+                milestone_index: None,
+                timestamp: None,
+                unresolved_output: None,
+            },
+            TransactionRecord {
+                message_id: MESSAGE_ID.to_string(),
+                transaction_id: TRANSACTION_ID.to_string(),
+                direction: TransactionDirection::Outgoing,
+                amount: 400,
+                counterparties: vec![SENDER.to_string(), RECIPIENT.to_string()],
+                milestone_index: Some(42),
+                timestamp: Some(1_609_459_200),
+                unresolved_output: None,
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_history_to_csv() {
+    let csv = history().to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "messageId,transactionId,direction,amount,counterparties,milestoneIndex,timestamp,unresolvedOutput"
+                .to_string(),
+            format!("{},{},incoming,1000,,,,", "0".repeat(64), "0".repeat(64)),
+            format!(
+                "{},{},outgoing,400,{};{},42,1609459200,",
+                MESSAGE_ID, TRANSACTION_ID, SENDER, RECIPIENT
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_history_to_json() {
+    let json: serde_json::Value = serde_json::from_str(&history().to_json().unwrap()).unwrap();
+    assert_eq!(
+        json[1],
+        serde_json::json!({
+            "messageId": MESSAGE_ID,
+            "transactionId": TRANSACTION_ID,
+            "direction": "outgoing",
+            "amount": 400,
+            "counterparties": [SENDER, RECIPIENT],
+            "milestoneIndex": 42,
+            "timestamp": 1_609_459_200,
+            "unresolvedOutput": null,
+        })
+    );
+    assert_eq!(json[0]["milestoneIndex"], serde_json::Value::Null);
+
+    let transactions: Vec<TransactionRecord> = serde_json::from_value(json).unwrap();
+    assert_eq!(transactions, history().transactions);
+}
+
+#[test]
+fn test_classify_transaction() {
+    let owned = vec![
+        parse_address(&"11".repeat(32)).unwrap(),
+        parse_address(&"22".repeat(32)).unwrap(),
+    ];
+    let other = parse_address(&"33".repeat(32)).unwrap();
+    let another = parse_address(&"44".repeat(32)).unwrap();
+
+    // Nothing spent from the owned addresses
+    assert_eq!(
+        classify_transaction(
+            &owned,
+            &[(other.clone(), 10)],
+            &[(owned[0].clone(), 7), (other.clone(), 3)]
+        ),
+        (TransactionDirection::Incoming, 7, vec![other.to_bech32()])
+    );
+    // Nothing sent to other addresses
+    assert_eq!(
+        classify_transaction(&owned, &[(owned[0].clone(), 10)], &[(owned[1].clone(), 10)]),
+        (TransactionDirection::Internal, 10, Vec::new())
+    );
+    // The remainder back to the owned addresses isn't part of the amount, and each recipient is listed once
+    assert_eq!(
+        classify_transaction(
+            &owned,
+            &[(owned[0].clone(), 10), (owned[1].clone(), 5)],
+            &[
+                (other.clone(), 4),
+                (another.clone(), 2),
+                (other.clone(), 1),
+                (owned[1].clone(), 8)
+            ],
+        ),
+        (
+            TransactionDirection::Outgoing,
+            7,
+            vec![other.to_bech32(), another.to_bech32()]
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_history_unresolved_spent_output() {
+    let address = parse_address(&"11".repeat(32)).unwrap();
+    // An output of the genesis snapshot spent by a transaction which sent nothing back to the address
+    let mut output = FakeOutput::new(0, 0, &address, 1_000).spent();
+    output.message_id = "0".repeat(64);
+    let (node, _ledger) = FakeNode::with_ledger(vec![output.clone()]);
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+
+    let history = client.get_address_history(&[address]).await.unwrap();
+    assert_eq!(history.transactions.len(), 2);
+    assert_eq!(history.transactions[0].direction, TransactionDirection::Incoming);
+    assert_eq!(history.transactions[0].unresolved_output, None);
+    assert_eq!(
+        history.transactions[1],
+        TransactionRecord {
+            message_id: String::new(),
+            transaction_id: String::new(),
+            direction: TransactionDirection::Outgoing,
+            amount: 1_000,
+            counterparties: Vec::new(),
+            milestone_index: None,
+            timestamp: None,
+            unresolved_output: Some(output.id()),
+        }
+    );
+}
@@ -0,0 +1,100 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{
+    api::{
+        BranchAndBound, ExactMatchPreferred, FirstFit, InputSelection, LargestFirst, SmallestFirst, INPUT_COUNT_MAX,
+    },
+    Error,
+};
+
+fn selected_sum(candidates: &[u64], selection: &[usize]) -> u64 {
+    selection.iter().map(|i| candidates[*i]).sum()
+}
+
+#[test]
+fn test_first_fit() {
+    let candidates = [5, 1, 10, 3];
+    assert_eq!(FirstFit.select(&candidates, 6, INPUT_COUNT_MAX).unwrap(), vec![0, 1]);
+}
+
+#[test]
+fn test_largest_first() {
+    let candidates = [5, 1, 10, 3];
+    assert_eq!(
+        LargestFirst.select(&candidates, 12, INPUT_COUNT_MAX).unwrap(),
+        vec![2, 0]
+    );
+}
+
+#[test]
+fn test_smallest_first() {
+    let candidates = [5, 1, 10, 3];
+    assert_eq!(
+        SmallestFirst.select(&candidates, 4, INPUT_COUNT_MAX).unwrap(),
+        vec![1, 3]
+    );
+}
+
+#[test]
+fn test_smallest_first_within_input_limit() {
+    // The three smallest candidates can't cover the amount with two inputs
+    let candidates = [1, 1, 1, 5, 6];
+    let selection = SmallestFirst.select(&candidates, 10, 2).unwrap();
+    assert_eq!(selection, vec![3, 4]);
+}
+
+#[test]
+fn test_exact_match_preferred() {
+    let candidates = [5, 7, 10, 3];
+    assert_eq!(
+        ExactMatchPreferred.select(&candidates, 7, INPUT_COUNT_MAX).unwrap(),
+        vec![1]
+    );
+    assert_eq!(
+        ExactMatchPreferred.select(&candidates, 8, INPUT_COUNT_MAX).unwrap(),
+        vec![2]
+    );
+    assert_eq!(
+        selected_sum(
+            &candidates,
+            &ExactMatchPreferred.select(&candidates, 20, INPUT_COUNT_MAX).unwrap()
+        ),
+        22
+    );
+}
+
+#[test]
+fn test_branch_and_bound_minimal_change() {
+    let candidates = [8, 5, 4, 3, 20];
+    let selection = BranchAndBound.select(&candidates, 12, INPUT_COUNT_MAX).unwrap();
+    assert_eq!(selected_sum(&candidates, &selection), 12);
+
+    let selection = BranchAndBound.select(&candidates, 10, INPUT_COUNT_MAX).unwrap();
+    assert_eq!(selected_sum(&candidates, &selection), 11);
+}
+
+#[test]
+fn test_not_enough_balance() {
+    match LargestFirst.select(&[1, 2], 4, INPUT_COUNT_MAX) {
+        Err(Error::NotEnoughBalance(3)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_too_many_inputs() {
+    let candidates = vec![1; INPUT_COUNT_MAX + 1];
+    for strategy in &[
+        &FirstFit as &dyn InputSelection,
+        &LargestFirst,
+        &SmallestFirst,
+        &ExactMatchPreferred,
+        &BranchAndBound,
+    ] {
+        match strategy.select(&candidates, INPUT_COUNT_MAX as u64 + 1, INPUT_COUNT_MAX) {
+            Err(Error::TooManyInputs(INPUT_COUNT_MAX)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}
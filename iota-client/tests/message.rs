@@ -0,0 +1,239 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use bee_message::prelude::{Indexation, MessageId, Payload};
+use common::{FakeNode, TIP1, TIP2};
+use iota_client::{Client, Error, NonceProvider, PowHandle};
+
+use std::{str::FromStr, thread, time::Duration};
+
+const PARENT1: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+const PARENT2: &str = "0000000000000000000000000000000000000000000000000000000000000002";
+
+fn client(node: &FakeNode) -> Client {
+    Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .tips_interval(Duration::from_millis(100))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_message_with_parents() {
+    let node = FakeNode::start();
+    let client = client(&node);
+    let parent1 = MessageId::from_str(PARENT1).unwrap();
+    let parent2 = MessageId::from_str(PARENT2).unwrap();
+
+    let message = client
+        .message()
+        .with_parents(parent1, parent2)
+        .with_index("HELLO")
+        .with_data(b"world".to_vec())
+        .finish()
+        .await
+        .unwrap();
+
+    assert_eq!(*message.parent1(), parent1);
+    assert_eq!(*message.parent2(), parent2);
+    assert_eq!(message.network_id(), client.get_network_id().await.unwrap());
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => assert_eq!(indexation.index(), "HELLO"),
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+    assert_eq!(node.requests("/api/v1/tips"), 0);
+}
+
+#[tokio::test]
+async fn test_message_with_tips() {
+    let node = FakeNode::start();
+    let client = client(&node);
+
+    let message = client.message().with_index("HELLO").finish().await.unwrap();
+
+    assert_eq!(*message.parent1(), MessageId::from_str(TIP1).unwrap());
+    assert_eq!(*message.parent2(), MessageId::from_str(TIP2).unwrap());
+    assert_eq!(node.requests("/api/v1/tips"), 1);
+}
+
+#[tokio::test]
+async fn test_message_payload_kind() {
+    let node = FakeNode::start();
+    let client = client(&node);
+    let indexation = Payload::Indexation(Box::new(Indexation::new(String::from("HELLO"), b"world").unwrap()));
+
+    let message = client
+        .message()
+        .with_payload(indexation.clone())
+        .finish()
+        .await
+        .unwrap();
+    match message.payload() {
+        Some(Payload::Indexation(indexation)) => assert_eq!(indexation.data(), b"world"),
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+
+    let message = client.message().finish().await.unwrap();
+    assert!(message.payload().is_none());
+
+    match client
+        .message()
+        .with_payload(indexation)
+        .with_index("HELLO")
+        .finish()
+        .await
+    {
+        Err(Error::InvalidParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match client.message().with_data(b"world".to_vec()).finish().await {
+        Err(Error::MissingParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_message_tips_refresh() {
+    let node = FakeNode::start();
+    let client = client(&node);
+
+    // The PoW can't finish, so it's restarted with fresh tips until the message is given up
+    let message = client.message().with_index("HELLO").with_pow_score(1e30).finish();
+    assert!(tokio::time::timeout(Duration::from_secs(1), message).await.is_err());
+
+    assert!(node.requests("/api/v1/tips") >= 2);
+}
+
+#[tokio::test]
+async fn test_message_pow_cancel() {
+    let node = FakeNode::start();
+    let client = client(&node);
+    let handle = PowHandle::new();
+
+    let handle_ = handle.clone();
+    tokio::spawn(async move {
+        tokio::time::delay_for(Duration::from_millis(200)).await;
+        handle_.cancel();
+    });
+    let message = client
+        .message()
+        .with_index("HELLO")
+        .with_pow_score(1e30)
+        .with_pow_handle(handle.clone())
+        .finish();
+
+    match tokio::time::timeout(Duration::from_secs(5), message).await {
+        Ok(Err(Error::PowCancelled)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    assert!(handle.progress().cancelled);
+}
+
+#[tokio::test]
+async fn test_message_pow_timeout() {
+    let node = FakeNode::start();
+    let client = client(&node);
+    let parent = MessageId::from_str(PARENT1).unwrap();
+
+    let message = client
+        .message()
+        .with_parents(parent, parent)
+        .with_pow_score(1e30)
+        .with_pow_timeout(Duration::from_millis(200))
+        .finish();
+
+    match tokio::time::timeout(Duration::from_secs(5), message).await {
+        Ok(Err(Error::PowTimeout)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn test_message_pow_interrupted_before_start() {
+    let node = FakeNode::start();
+    // The attempts are interrupted right after being spawned, usually before their miner starts
+    let client = Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .tips_interval(Duration::from_micros(1))
+        .build()
+        .unwrap();
+
+    let message = client
+        .message()
+        .with_index("HELLO")
+        .with_pow_score(1e30)
+        .with_pow_timeout(Duration::from_millis(500))
+        .finish();
+
+    // No attempt is left mining, so the timeout is reached
+    match tokio::time::timeout(Duration::from_secs(10), message).await {
+        Ok(Err(Error::PowTimeout)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+// Provider which never finds a nonce and only gives up once it's told to stop
+struct StoppableProvider;
+
+impl NonceProvider for StoppableProvider {
+    fn nonce(&self, _bytes: &[u8], _target_score: f64, handle: &PowHandle) -> iota_client::Result<u64> {
+        while !handle.is_stopped() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        Err(Error::Pow(String::from("stopped")))
+    }
+}
+
+#[tokio::test]
+async fn test_message_pow_provider_timeout() {
+    let node = FakeNode::start();
+    let client = Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .pow_provider(StoppableProvider)
+        .build()
+        .unwrap();
+    let parent = MessageId::from_str(PARENT1).unwrap();
+
+    let message = client
+        .message()
+        .with_parents(parent, parent)
+        .with_pow_timeout(Duration::from_millis(200))
+        .finish();
+
+    match tokio::time::timeout(Duration::from_secs(5), message).await {
+        Ok(Err(Error::PowTimeout)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+// Provider which fails at once, like a remote provider out of credits
+struct FailingProvider;
+
+impl NonceProvider for FailingProvider {
+    fn nonce(&self, _bytes: &[u8], _target_score: f64, _handle: &PowHandle) -> iota_client::Result<u64> {
+        Err(Error::Pow(String::from("out of credits")))
+    }
+}
+
+#[tokio::test]
+async fn test_message_pow_provider_error() {
+    let node = FakeNode::start();
+    let client = Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .pow_provider(FailingProvider)
+        .build()
+        .unwrap();
+    let parent = MessageId::from_str(PARENT1).unwrap();
+
+    // The error of the provider is kept instead of the one of the message builder
+    match client.message().with_parents(parent, parent).finish().await {
+        Err(Error::Pow(message)) => assert_eq!(message, "out of credits"),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use bee_message::prelude::{Address, Ed25519Address};
+use iota_client::{
+    api::Account,
+    mnemonic::{generate_mnemonic, mnemonic_to_seed, mnemonic_to_seed_bytes, verify_mnemonic},
+    BIP32Path, Client, Error, Signer,
+};
+
+// Test vectors of BIP39, shared by the wallets deriving their seed from a mnemonic
+const VECTORS: [(&str, &str); 3] = [
+    (
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553\
+         1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+    ),
+    (
+        "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6f\
+         a457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+    ),
+    (
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+         abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+        "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd30971\
+         70af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+    ),
+];
+
+// Ed25519 addresses of the mnemonics of the vectors, derived with SLIP-0010 from all the bytes of their BIP39 seed:
+// (vector, passphrase, account, internal, index, address)
+const ADDRESS_VECTORS: [(usize, &str, u32, bool, usize, &str); 8] = [
+    (
+        0,
+        "",
+        0,
+        false,
+        0,
+        "365b74f27ca7c6d7ce019d73042f85cc4627e1aeec2b7822994e16010234e576",
+    ),
+    (
+        0,
+        "",
+        0,
+        false,
+        1,
+        "d82f7aea5c9bc5629957fd4b50fedb9228a50890a7341a5edd08784a27c09dbc",
+    ),
+    (
+        0,
+        "",
+        0,
+        true,
+        0,
+        "f6ad271e81907121e0ed6d00fb493bb1ea69c2169fe6d7ac4eae040c68090998",
+    ),
+    (
+        0,
+        "",
+        1,
+        false,
+        0,
+        "a7cac6510ccf50c6faea22e369b2e9de8aab58502a377a0a21b76b75785fe9af",
+    ),
+    (
+        1,
+        "TREZOR",
+        0,
+        false,
+        0,
+        "6d742cec19b840c307997995c722010a088f0be72c2297f889d9187529cf26f6",
+    ),
+    (
+        1,
+        "TREZOR",
+        0,
+        false,
+        1,
+        "a56179b4ef7ce6038d28e6710e7713f602e03f4454c380b6e1156d68eca02431",
+    ),
+    (
+        1,
+        "TREZOR",
+        0,
+        true,
+        0,
+        "b1220a5d17104fb236fd83520ccd1682b05453609cdab434b6c39587b1d14d05",
+    ),
+    (
+        1,
+        "TREZOR",
+        1,
+        false,
+        0,
+        "2e6a47ca2abbd120acd1d0bcd548f74c4d5471b39cda0da6e917cba087c43a0e",
+    ),
+];
+
+fn address(hex_address: &str) -> Address {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex_address, &mut bytes).unwrap();
+    Address::Ed25519(Ed25519Address::new(bytes))
+}
+
+#[test]
+fn test_mnemonic_to_seed_vectors() {
+    for (mnemonic, seed) in VECTORS.iter() {
+        verify_mnemonic(mnemonic).unwrap();
+        assert_eq!(
+            hex::encode(&mnemonic_to_seed_bytes(mnemonic, "TREZOR").unwrap()[..]),
+            *seed
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mnemonic_to_address_vectors() {
+    let client = Client::builder().node("http://0.0.0.0:14265").unwrap().build().unwrap();
+    for (vector, passphrase, account, internal, index, expected) in ADDRESS_VECTORS.iter() {
+        let seed = mnemonic_to_seed(VECTORS[*vector].0, passphrase).unwrap();
+        let addresses = client
+            .find_addresses(&seed)
+            .account(Account::new(*account))
+            .internal(*internal)
+            .range(*index..*index + 1)
+            .get()
+            .await
+            .unwrap();
+        assert_eq!(addresses, vec![address(expected)]);
+    }
+}
+
+#[tokio::test]
+async fn test_mnemonic_to_seed_derivation() {
+    let (mnemonic, _) = VECTORS[1];
+    let path = BIP32Path::from_str("m/44'/4218'/0'/0'/0'").unwrap();
+    // Whitespace in the mnemonic doesn't matter
+    let expected = mnemonic_to_seed(mnemonic, "TREZOR").unwrap();
+    let recovered = mnemonic_to_seed(&format!("  {}\n", mnemonic.replace(' ', "\t")), "TREZOR").unwrap();
+    assert_eq!(
+        recovered.public_key(&path).await.unwrap(),
+        expected.public_key(&path).await.unwrap()
+    );
+    // Another passphrase gives another seed
+    let other = mnemonic_to_seed(mnemonic, "").unwrap();
+    assert_ne!(
+        other.public_key(&path).await.unwrap(),
+        expected.public_key(&path).await.unwrap()
+    );
+}
+
+#[test]
+fn test_generate_mnemonic() {
+    let mnemonic = generate_mnemonic();
+    assert_eq!(mnemonic.split(' ').count(), 24);
+    verify_mnemonic(&mnemonic).unwrap();
+    assert_ne!(mnemonic, generate_mnemonic());
+}
+
+#[test]
+fn test_invalid_mnemonic() {
+    // Wrong checksum
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+    match verify_mnemonic(mnemonic) {
+        Err(Error::Mnemonic(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    // Word not in the wordlist
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon iota";
+    assert!(verify_mnemonic(mnemonic).is_err());
+    assert!(mnemonic_to_seed(mnemonic, "").is_err());
+}
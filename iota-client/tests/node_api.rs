@@ -42,25 +42,13 @@ async fn test_get_tips() {
 #[ignore]
 #[tokio::test]
 async fn test_post_message_with_indexation() {
-    let index = Indexation::new(String::from("Hello"), &[]).unwrap();
-
     let client = iota_client::Client::builder()
         .node("http://0.0.0.0:14265")
         .unwrap()
         .build()
         .unwrap();
 
-    let tips = client.get_tips().await.unwrap();
-
-    let message = Message::builder()
-        .with_network_id(client.get_network_id().await.unwrap())
-        .with_parent1(tips.0)
-        .with_parent2(tips.1)
-        .with_payload(Payload::Indexation(Box::new(index)))
-        .finish()
-        .unwrap();
-
-    let r = client.post_message(&message).await.unwrap();
+    let r = client.message().with_index("Hello").post().await.unwrap();
 
     println!("{}", r);
 }
@@ -82,7 +70,7 @@ async fn test_post_message_with_transaction() {
 
     // Insert your account path. Note that index must be hardened(like 0', 123').
     let path = BIP32Path::from_str("m/").unwrap();
-    let message_id = iota
+    let (message_id, remainder_address) = iota
         .send(&seed)
         .path(&path)
         // Insert the output address and ampunt to spent. The amount cannot be zero.
@@ -99,7 +87,10 @@ async fn test_post_message_with_transaction() {
         .post()
         .await
         .unwrap();
-    println!("Message ID: {:?}", message_id);
+    println!(
+        "Message ID: {:?}, remainder address: {:?}",
+        message_id, remainder_address
+    );
 }
 
 #[ignore]
@@ -261,3 +252,216 @@ async fn test_get_milestone() {
 
     println!("{:#?}", r);
 }
+
+#[ignore]
+#[tokio::test]
+async fn test_notarize() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let document = b"document to notarize";
+    let receipt = client.notarize(&document[..]).post().await.unwrap();
+    println!("{:#?}", receipt);
+
+    let verification = client.verify_notarization(&receipt, &document[..]).get().await.unwrap();
+    assert!(verification.is_valid(false));
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_discover_account() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let used_addresses = client
+        .discover_account(&seed)
+        .account(iota_client::api::Account::new(0))
+        .gap_limit(5)
+        .get()
+        .await
+        .unwrap();
+
+    for used in used_addresses {
+        println!(
+            "{} {} {} {}",
+            used.address.to_bech32(),
+            used.index,
+            used.internal,
+            used.balance
+        );
+    }
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_get_balance_report() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let report = client
+        .get_balance(&seed)
+        .account(iota_client::api::Account::new(0))
+        .get_report()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.total_balance,
+        report.addresses.iter().map(|address| address.balance).sum::<u64>()
+    );
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_get_unspent_address_reserved() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let path = BIP32Path::from_str("m/").unwrap();
+    let (first, first_index) = client
+        .get_unspent_address(&seed)
+        .path(&path)
+        .reserve(true)
+        .get()
+        .await
+        .unwrap();
+    let (second, second_index) = client
+        .get_unspent_address(&seed)
+        .path(&path)
+        .reserve(true)
+        .get()
+        .await
+        .unwrap();
+    assert_ne!(first, second);
+    assert!(second_index > first_index);
+
+    // A released address is returned again
+    client.release_address(&first);
+    let (address, _) = client
+        .get_unspent_address(&seed)
+        .path(&path)
+        .reserve(true)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(address, first);
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_consolidate() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let message_ids = client
+        .consolidate(&seed)
+        .account(iota_client::api::Account::new(0))
+        .output_threshold(2)
+        .post()
+        .await
+        .unwrap();
+
+    println!("{:#?}", message_ids);
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_batch_send() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let payouts = iota_client::api::payouts_from_csv(
+        "address,amount\n\
+         6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1f92,1000000\n\
+         6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1f92,2000000",
+    )
+    .unwrap();
+    let (results, error) = client
+        .batch_send(&seed)
+        .account(iota_client::api::Account::new(0))
+        .payouts(payouts)
+        .max_outputs(1)
+        .post()
+        .await;
+
+    println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    assert!(error.is_none(), "{:?}", error);
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_get_transaction_history() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let seed = Seed::from_ed25519_bytes(
+        &hex::decode("256a818b2aac458941f7274985a410e57fb750f3a3a67969ece5bd9ae7eef5b2").unwrap(), // Insert your seed
+    )
+    .unwrap();
+    let history = client
+        .get_transaction_history(&seed)
+        .account(iota_client::api::Account::new(0))
+        .get()
+        .await
+        .unwrap();
+
+    println!("{}", history.to_csv());
+}
+
+#[ignore]
+#[tokio::test]
+async fn test_get_address_history() {
+    let client = iota_client::Client::builder()
+        .node("http://0.0.0.0:14265")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let address =
+        iota_client::parse_address("6920b176f613ec7be59e68fc68f597eb3393af80f74c7c3db78198147d5f1f92").unwrap();
+    let history = client.get_address_history(&[address]).await.unwrap();
+
+    println!("{}", history.to_json().unwrap());
+}
@@ -0,0 +1,81 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::FakeNode;
+use iota_client::{api::NOTARIZATION_ALGORITHM, Client};
+
+use std::io::{Cursor, Read};
+
+// BLAKE2b-256 of no data
+const EMPTY_HASH: &str = "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8";
+
+fn client(node: &FakeNode) -> Client {
+    Client::builder()
+        .node(&node.url)
+        .unwrap()
+        .min_pow_score(1f64)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_notarize_hash() {
+    let (node, _) = FakeNode::storing();
+    let client = client(&node);
+
+    let receipt = client.notarize(Cursor::new(Vec::new())).post().await.unwrap();
+    assert_eq!(receipt.algorithm, NOTARIZATION_ALGORITHM);
+    assert_eq!(receipt.hash, EMPTY_HASH);
+
+    // The document is streamed through the hasher, across several reads
+    let document = vec![1u8; 100_000];
+    let streamed = client
+        .notarize(std::io::repeat(1).take(document.len() as u64))
+        .post()
+        .await
+        .unwrap();
+    let receipt = client.notarize(Cursor::new(document)).post().await.unwrap();
+    assert_eq!(streamed.hash, receipt.hash);
+    assert_ne!(receipt.hash, EMPTY_HASH);
+}
+
+#[tokio::test]
+async fn test_verify_notarization() {
+    let (node, _) = FakeNode::storing();
+    let client = client(&node);
+    let document = b"document to notarize";
+
+    let receipt = client
+        .notarize(&document[..])
+        .metadata(serde_json::json!({ "name": "document.txt" }))
+        .post()
+        .await
+        .unwrap();
+
+    let verification = client.verify_notarization(&receipt, &document[..]).get().await.unwrap();
+    assert!(verification.hash_matches);
+    assert!(verification.message_matches);
+    assert!(verification.is_valid(false));
+    assert!(!verification.is_valid(true));
+
+    let verification = client
+        .verify_notarization(&receipt, &b"other document"[..])
+        .get()
+        .await
+        .unwrap();
+    assert!(!verification.hash_matches);
+    assert!(!verification.is_valid(false));
+
+    // The receipt doesn't match the message anymore
+    let mut tampered = receipt.clone();
+    tampered.timestamp += 1;
+    let verification = client
+        .verify_notarization(&tampered, &document[..])
+        .get()
+        .await
+        .unwrap();
+    assert!(verification.hash_matches);
+    assert!(!verification.message_matches);
+}
@@ -0,0 +1,126 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use bee_message::prelude::{Address, Ed25519Address};
+use common::{FakeNode, FakeOutput};
+use iota_client::{
+    api::{derive_address_paths, sign_transaction, AddressPath, PreparedTransaction, RemainderStrategy},
+    BIP32Path, Bech32Address, Client, Error, Seed,
+};
+
+use std::{num::NonZeroU64, str::FromStr};
+
+const PATH: &str = "m/44'/4218'/0'/0'";
+
+fn recipient() -> Address {
+    Address::Ed25519(Ed25519Address::new([7; 32]))
+}
+
+fn copy(prepared: &PreparedTransaction) -> PreparedTransaction {
+    serde_json::from_str(&serde_json::to_string(prepared).unwrap()).unwrap()
+}
+
+async fn assert_rejected(prepared: PreparedTransaction, seed: &Seed) {
+    match sign_transaction(prepared, seed).await {
+        Err(Error::InvalidParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r.map(|signed| signed.payload)),
+    }
+}
+
+// Prepare a transaction sending 4 of the 10 tokens on the first address, without the seed
+async fn prepare(addresses: &[AddressPath], remainder: Option<RemainderStrategy>) -> PreparedTransaction {
+    let input_address = Address::try_from_bech32(&addresses[0].address).unwrap();
+    let (node, _) = FakeNode::with_ledger(vec![FakeOutput::new(1, 0, &input_address, 10)]);
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let mut builder = client
+        .prepare_transaction(addresses)
+        .output(recipient(), NonZeroU64::new(4).unwrap());
+    if let Some(remainder) = remainder {
+        builder = builder.remainder(remainder);
+    }
+    builder.prepare().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_derive_address_paths() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let node = FakeNode::start();
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+
+    let address_paths = derive_address_paths(&seed, &path, 2..4).await.unwrap();
+    let addresses = client
+        .find_addresses(&seed)
+        .path(&path)
+        .range(2..4)
+        .get()
+        .await
+        .unwrap();
+    for (address_path, (address, index)) in address_paths.iter().zip(addresses.iter().zip(2..4)) {
+        assert_eq!(address_path.address, address.to_bech32());
+        let expected = BIP32Path::from_str(&format!("{}/{}'", PATH, index)).unwrap();
+        assert_eq!(address_path.path, expected.to_string());
+    }
+}
+
+#[tokio::test]
+async fn test_sign_prepared_transaction() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let addresses = derive_address_paths(&seed, &BIP32Path::from_str(PATH).unwrap(), 0..3)
+        .await
+        .unwrap();
+    let prepared = prepare(&addresses, None).await;
+
+    assert_eq!(prepared.inputs[0].path, addresses[0].path);
+    // The remainder goes to the first unused address
+    let remainder = prepared.outputs.iter().find(|output| output.remainder).unwrap();
+    assert_eq!(remainder.address, addresses[1].address);
+    assert_eq!(remainder.path.as_ref(), Some(&addresses[1].path));
+    assert_eq!(remainder.amount, 6);
+
+    sign_transaction(copy(&prepared), &seed).await.unwrap();
+    // The keys of another seed don't match the addresses
+    assert_rejected(copy(&prepared), &Seed::from_ed25519_bytes(&[2; 32]).unwrap()).await;
+}
+
+#[tokio::test]
+async fn test_sign_tampered_transaction() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let addresses = derive_address_paths(&seed, &BIP32Path::from_str(PATH).unwrap(), 0..3)
+        .await
+        .unwrap();
+    let prepared = prepare(&addresses, None).await;
+    let remainder = prepared.outputs.iter().position(|output| output.remainder).unwrap();
+
+    // Input signed with the key of another address
+    let mut tampered = copy(&prepared);
+    tampered.inputs[0].path = addresses[2].path.clone();
+    assert_rejected(tampered, &seed).await;
+
+    // Remainder listed with the path of another address
+    let mut tampered = copy(&prepared);
+    tampered.outputs[remainder].path = Some(addresses[2].path.clone());
+    assert_rejected(tampered, &seed).await;
+
+    // Remainder without path
+    let mut tampered = copy(&prepared);
+    tampered.outputs[remainder].path = None;
+    assert_rejected(tampered, &seed).await;
+
+    // An output to an address of someone else passed off as the remainder
+    let other = Address::Ed25519(Ed25519Address::new([8; 32]));
+    let prepared = prepare(&addresses, Some(RemainderStrategy::Address(other.clone()))).await;
+    assert!(prepared.outputs.iter().all(|output| !output.remainder));
+    sign_transaction(copy(&prepared), &seed).await.unwrap();
+    let mut tampered = copy(&prepared);
+    let output = tampered
+        .outputs
+        .iter_mut()
+        .find(|output| output.address == other.to_bech32())
+        .unwrap();
+    output.remainder = true;
+    output.path = Some(addresses[1].path.clone());
+    assert_rejected(tampered, &seed).await;
+}
@@ -0,0 +1,83 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::{NonceProvider, PowHandle, RemotePowProvider};
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+// Serve a single request of the remote PoW protocol and return the request body.
+fn serve_once(status: &'static str, body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/pow", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut request = vec![0; content_length];
+        reader.read_exact(&mut request).unwrap();
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    (url, handle)
+}
+
+#[test]
+fn test_remote_pow_provider() {
+    let (url, server) = serve_once("200 OK", r#"{"nonce":"18446744073709551615"}"#);
+
+    let provider = RemotePowProvider::new(&url).unwrap();
+    let nonce = provider
+        .nonce(&[1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0], 4000f64, &PowHandle::new())
+        .unwrap();
+    assert_eq!(nonce, u64::MAX);
+
+    let request: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+    assert_eq!(request["bytes"], "0102030000000000000000");
+    assert_eq!(request["targetScore"], 4000f64);
+}
+
+#[test]
+fn test_remote_pow_provider_error() {
+    let (url, server) = serve_once("503 Service Unavailable", r#"{"error":"busy"}"#);
+
+    let provider = RemotePowProvider::new(&url).unwrap();
+    assert!(provider.nonce(&[0; 8], 4000f64, &PowHandle::new()).is_err());
+    server.join().unwrap();
+}
+
+#[test]
+fn test_remote_pow_provider_stopped() {
+    let handle = PowHandle::new();
+    handle.cancel();
+
+    // Nothing listens on the url, the provider gives up before sending the request
+    let provider = RemotePowProvider::new("http://127.0.0.1:1/pow").unwrap();
+    match provider.nonce(&[0; 8], 4000f64, &handle) {
+        Err(iota_client::Error::Pow(message)) => assert_eq!(message, "PoW stopped before the request"),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::FakeNode;
+use iota_client::{Client, ClientBuilder, Error, NonceProvider, PayloadKind, PowHandle};
+
+use std::sync::{Arc, Mutex};
+
+const NONCE: u64 = 42;
+
+struct FixedProvider;
+
+impl NonceProvider for FixedProvider {
+    fn nonce(&self, _bytes: &[u8], _target_score: f64, _handle: &PowHandle) -> iota_client::Result<u64> {
+        Ok(NONCE)
+    }
+}
+
+// Start a node with the features, storing the bodies of the posted messages
+fn node(features: &'static [&'static str]) -> (FakeNode, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let posted = Arc::new(Mutex::new(Vec::new()));
+    let posted_ = posted.clone();
+    let node = FakeNode::with_handler(move |method, path, body| match (method, path) {
+        ("GET", "/api/v1/info") => Some((200, common::info(features))),
+        ("POST", "/api/v1/messages") => {
+            posted_.lock().unwrap().push(serde_json::from_slice(body).unwrap());
+            Some((201, format!(r#"{{"data":{{"messageId":"{}"}}}}"#, "0".repeat(64))))
+        }
+        _ => None,
+    });
+    (node, posted)
+}
+
+fn builder(node: &FakeNode) -> ClientBuilder {
+    Client::builder().node(&node.url).unwrap().local_pow(false)
+}
+
+#[tokio::test]
+async fn test_post_message_with_remote_pow() {
+    let (node, posted) = node(&["PoW"]);
+    let client = builder(&node).build().unwrap();
+
+    client.message().with_index("Hello").post().await.unwrap();
+
+    assert_eq!(posted.lock().unwrap()[0]["nonce"], "");
+}
+
+#[tokio::test]
+async fn test_post_message_with_provider_of_kind() {
+    let (node, posted) = node(&["PoW"]);
+    let client = builder(&node)
+        .pow_provider_for(PayloadKind::Indexation, FixedProvider)
+        .build()
+        .unwrap();
+
+    // The indexation message gets the nonce of its provider, the message without payload is left to the node
+    client.message().with_index("Hello").post().await.unwrap();
+    client.message().post().await.unwrap();
+
+    let posted = posted.lock().unwrap();
+    assert_eq!(posted[0]["nonce"], NONCE.to_string());
+    assert_eq!(posted[1]["nonce"], "");
+}
+
+#[tokio::test]
+async fn test_remote_pow_node_filter() {
+    // The node doesn't do the PoW, so it's only usable when every kind of message has a provider
+    let (node, _) = node(&[]);
+    let client = builder(&node)
+        .pow_provider_for(PayloadKind::Indexation, FixedProvider)
+        .build()
+        .unwrap();
+    match client.get_tips().await {
+        Err(Error::SyncedNodePoolEmpty) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let mut every_kind = builder(&node);
+    for kind in PayloadKind::ALL.iter() {
+        every_kind = every_kind.pow_provider_for(*kind, FixedProvider);
+    }
+    every_kind.build().unwrap().get_tips().await.unwrap();
+
+    let client = builder(&node).pow_provider(FixedProvider).build().unwrap();
+    client.get_tips().await.unwrap();
+}
@@ -0,0 +1,103 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use bee_message::prelude::{Address, Ed25519Address};
+use common::{FakeNode, FakeOutput};
+use iota_client::{BIP32Path, Client, Error, Seed};
+
+use std::{num::NonZeroU64, str::FromStr};
+
+const PATH: &str = "m/44'/4218'/0'/0'";
+
+fn client(node: &FakeNode) -> Client {
+    Client::builder().node(&node.url).unwrap().build().unwrap()
+}
+
+fn recipient() -> Address {
+    Address::Ed25519(Ed25519Address::new([7; 32]))
+}
+
+#[tokio::test]
+async fn test_send_duplicate_input() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let node = FakeNode::start();
+    let client = client(&node);
+    let address = client
+        .find_addresses(&seed)
+        .path(&path)
+        .range(0..1)
+        .get()
+        .await
+        .unwrap();
+    let output = FakeOutput::new(1, 0, &address[0], 10);
+
+    let result = client
+        .send(&seed)
+        .path(&path)
+        .output(recipient(), NonZeroU64::new(10).unwrap())
+        .input(output.input())
+        .inputs(vec![output.input()])
+        .prepare()
+        .await;
+    match result {
+        Err(Error::InvalidParameter(message)) => assert!(message.contains(&output.id()), "{}", message),
+        r => panic!("unexpected result {:?}", r.map(|prepared| prepared.inputs)),
+    }
+}
+
+#[tokio::test]
+async fn test_send_remainder_address_search() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = client(&node);
+    let addresses = client
+        .find_addresses(&seed)
+        .path(&path)
+        .range(0..6)
+        .get()
+        .await
+        .unwrap();
+    // The input is on the first address, and the next three addresses were already used
+    let mut outputs = vec![FakeOutput::new(1, 0, &addresses[0], 10)];
+    for (transaction, address) in addresses[1..4].iter().enumerate() {
+        outputs.push(FakeOutput::new(transaction as u64 + 2, 0, address, 1).spent());
+    }
+    *ledger.outputs.lock().unwrap() = outputs.clone();
+
+    // No unused address is left in the input range, so the search stops
+    let result = client
+        .send(&seed)
+        .path(&path)
+        .output(recipient(), NonZeroU64::new(4).unwrap())
+        .input(outputs[0].input())
+        .input_range(0..4)
+        .gap_limit(2)
+        .prepare()
+        .await;
+    match result {
+        Err(Error::UnusedAddressNotFound(range)) => assert_eq!(range, "0..4"),
+        r => panic!("unexpected result {:?}", r.map(|prepared| prepared.outputs)),
+    }
+
+    let prepared = client
+        .send(&seed)
+        .path(&path)
+        .output(recipient(), NonZeroU64::new(4).unwrap())
+        .input(outputs[0].input())
+        .input_range(0..6)
+        .gap_limit(2)
+        .prepare()
+        .await
+        .unwrap();
+    let remainder = prepared.outputs.iter().find(|output| output.remainder).unwrap();
+    assert_eq!(remainder.address, addresses[4].to_bech32());
+    assert_eq!(remainder.amount, 6);
+    assert_eq!(
+        prepared.inputs[0].path,
+        BIP32Path::from_str(&format!("{}/0'", PATH)).unwrap().to_string()
+    );
+}
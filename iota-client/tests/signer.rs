@@ -0,0 +1,181 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::executor::block_on;
+use iota_client::{
+    keystore,
+    signing::{RemoteSignerRequest, RemoteSignerResponse},
+    BIP32Path, RemoteSigner, Seed, Signer,
+};
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+const SEED: [u8; 32] = [7u8; 32];
+
+fn keystore_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("iota-client-{}-{}.json", name, std::process::id()))
+}
+
+// Serve the requests of the remote signer protocol with the seed, one per connection.
+fn serve(requests: usize) -> (std::net::SocketAddr, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        let seed = Seed::from_ed25519_bytes(&SEED).unwrap();
+        for _ in 0..requests {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+
+            let mut response = RemoteSignerResponse::default();
+            match serde_json::from_str(&line).unwrap() {
+                RemoteSignerRequest::PublicKey { path } => {
+                    let path = BIP32Path::from_str(&path).unwrap();
+                    response.public_key = Some(hex::encode(block_on(seed.public_key(&path)).unwrap()));
+                }
+                RemoteSignerRequest::Sign { path, essence } => {
+                    let path = BIP32Path::from_str(&path).unwrap();
+                    let signature = block_on(seed.sign(&path, &hex::decode(essence).unwrap())).unwrap();
+                    response.signature = Some(hex::encode(&signature[..]));
+                }
+            }
+            let mut line = serde_json::to_string(&response).unwrap();
+            line.push('\n');
+            reader.get_mut().write_all(line.as_bytes()).unwrap();
+        }
+    });
+
+    (address, handle)
+}
+
+#[tokio::test]
+async fn test_keystore_roundtrip() {
+    let path = keystore_path("roundtrip");
+    keystore::create(&path, &SEED, "password").unwrap();
+    let signer = keystore::open(&path, "password").unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let seed = Seed::from_ed25519_bytes(&SEED).unwrap();
+    let bip32_path = BIP32Path::from_str("m/44'/4218'/0'/0'/0'").unwrap();
+    assert_eq!(
+        signer.public_key(&bip32_path).await.unwrap(),
+        seed.public_key(&bip32_path).await.unwrap()
+    );
+    assert_eq!(
+        &signer.sign(&bip32_path, b"essence").await.unwrap()[..],
+        &seed.sign(&bip32_path, b"essence").await.unwrap()[..]
+    );
+}
+
+#[test]
+fn test_keystore_wrong_password() {
+    let path = keystore_path("wrong-password");
+    keystore::create(&path, &SEED, "password").unwrap();
+    let result = keystore::open(&path, "passw0rd");
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_keystore_no_overwrite() {
+    let path = keystore_path("no-overwrite");
+    keystore::create(&path, &SEED, "password").unwrap();
+    let result = keystore::create(&path, &[8u8; 32], "other");
+    let reopened = keystore::open(&path, "password");
+    let name = path.file_name().unwrap().to_str().unwrap().to_string();
+    let tmp_left = std::fs::read_dir(std::env::temp_dir()).unwrap().any(|entry| {
+        entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&format!("{}.", name))
+    });
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(result, Err(iota_client::Error::Keystore(_))));
+    // The first keystore is left untouched
+    assert!(reopened.is_ok());
+    assert!(!tmp_left);
+}
+
+#[test]
+fn test_keystore_change_password() {
+    let path = keystore_path("change-password");
+    keystore::create(&path, &SEED, "password").unwrap();
+    keystore::change_password(&path, "password", "new password").unwrap();
+    let old = keystore::open(&path, "password");
+    let new = keystore::open(&path, "new password");
+    std::fs::remove_file(&path).unwrap();
+    assert!(old.is_err());
+    assert!(new.is_ok());
+}
+
+#[tokio::test]
+async fn test_keystore_backup() {
+    let path = keystore_path("backup-source");
+    let backup = keystore_path("backup");
+    let imported = keystore_path("backup-imported");
+    keystore::create(&path, &SEED, "password").unwrap();
+    keystore::export(&path, "password", &backup, "backup password").unwrap();
+    // An existing keystore isn't overwritten
+    assert!(keystore::import(&backup, "backup password", &path, "password").is_err());
+    keystore::import(&backup, "backup password", &imported, "other password").unwrap();
+    let signer = keystore::open(&imported, "other password").unwrap();
+    for file in &[path, backup, imported] {
+        std::fs::remove_file(file).unwrap();
+    }
+
+    let bip32_path = BIP32Path::from_str("m/44'/4218'/0'/0'/0'").unwrap();
+    assert_eq!(
+        signer.public_key(&bip32_path).await.unwrap(),
+        Seed::from_ed25519_bytes(&SEED)
+            .unwrap()
+            .public_key(&bip32_path)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_public_keys_batch() {
+    let paths: Vec<BIP32Path> = (0..500)
+        .map(|index| BIP32Path::from_str(&format!("m/44'/4218'/0'/0'/{}'", index)).unwrap())
+        .collect();
+    let seed = Seed::from_ed25519_bytes(&SEED).unwrap();
+    let zeroizing_seed = keystore::ZeroizingSeed::from_bytes(&SEED).unwrap();
+    let signers: [&dyn Signer; 2] = [&seed, &zeroizing_seed];
+
+    // The batch is derived across threads, in the order of the paths
+    for signer in signers.iter() {
+        let batch = signer.public_keys(&paths).await.unwrap();
+        let mut serial = Vec::new();
+        for path in &paths {
+            serial.push(signer.public_key(path).await.unwrap());
+        }
+        assert_eq!(batch, serial);
+    }
+}
+
+#[tokio::test]
+async fn test_remote_signer() {
+    let (address, server) = serve(2);
+    let signer = RemoteSigner::new(address);
+
+    let seed = Seed::from_ed25519_bytes(&SEED).unwrap();
+    let path = BIP32Path::from_str("m/44'/4218'/0'/0'/1'").unwrap();
+    assert_eq!(
+        signer.public_key(&path).await.unwrap(),
+        seed.public_key(&path).await.unwrap()
+    );
+    assert_eq!(
+        &signer.sign(&path, b"essence").await.unwrap()[..],
+        &seed.sign(&path, b"essence").await.unwrap()[..]
+    );
+    server.join().unwrap();
+}
@@ -0,0 +1,30 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_client::Topic;
+
+#[test]
+fn test_topic_indexation() {
+    assert!(Topic::new("messages/indexation/48656c6c6f").is_ok());
+    assert!(Topic::new(format!("messages/indexation/{}", "ab".repeat(64))).is_ok());
+    assert!(Topic::new(format!("messages/indexation/{}", "ab".repeat(65))).is_err());
+    assert!(Topic::new("messages/indexation/Hello").is_err());
+    // An odd number of hex characters isn't a whole number of bytes
+    assert!(Topic::new("messages/indexation/abc").is_err());
+    assert!(Topic::new("messages/indexation/a").is_err());
+}
+
+#[test]
+fn test_topic_anchored() {
+    let message_id = "a008ce3354591950232c0dacdfcb17c4f6457c5bf407eff1befaab5fa7b59b7a";
+    assert!(Topic::new(format!("messages/{}/metadata", message_id)).is_ok());
+    for topic in &[
+        format!("messages/{}/metadata/extra", message_id),
+        format!("prefix/messages/{}/metadata", message_id),
+        String::from("messages/indexation/48656c6c6f/#"),
+        String::from("messages/indexation/48656c6c6f zz"),
+        String::from("messages/indexation/"),
+    ] {
+        assert!(Topic::new(topic.as_str()).is_err(), "{}", topic);
+    }
+}
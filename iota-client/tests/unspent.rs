@@ -0,0 +1,48 @@
+// Copyright 2020 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{FakeNode, FakeOutput};
+use iota_client::{BIP32Path, Client, Error, Seed};
+
+use std::str::FromStr;
+
+const PATH: &str = "m/44'/4218'/0'/0'";
+
+#[tokio::test]
+async fn test_unspent_address_gap_limit() {
+    let seed = Seed::from_ed25519_bytes(&[1; 32]).unwrap();
+    let path = BIP32Path::from_str(PATH).unwrap();
+    let (node, ledger) = FakeNode::with_ledger(Vec::new());
+    let client = Client::builder().node(&node.url).unwrap().build().unwrap();
+    let addresses = client
+        .find_addresses(&seed)
+        .path(&path)
+        .range(0..5)
+        .get()
+        .await
+        .unwrap();
+    // The first three addresses received outputs
+    *ledger.outputs.lock().unwrap() = (0..3)
+        .map(|index| FakeOutput::new(index as u64, 0, &addresses[index], 10))
+        .collect();
+
+    let (address, index) = client
+        .get_unspent_address(&seed)
+        .path(&path)
+        .gap_limit(2)
+        .get()
+        .await
+        .unwrap();
+    assert_eq!((address, index), (addresses[3].clone(), 3));
+    // The search stopped after the second batch of two addresses
+    let outputs_path = |index: usize| format!("/api/v1/addresses/{}/outputs", addresses[index].to_bech32());
+    assert_eq!(node.requests(&outputs_path(3)), 1);
+    assert_eq!(node.requests(&outputs_path(4)), 0);
+
+    match client.get_unspent_address(&seed).path(&path).gap_limit(0).get().await {
+        Err(Error::InvalidParameter(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}